@@ -0,0 +1,132 @@
+//! Compile the qt resources into a binary rcc file, which is embedded in the
+//! crate and registered via `pbgui_tree::init_resources`. If qt's rcc tool is
+//! not available, fall back on the checked in `resources/pbgui_tree.rcc`,
+//! which may be regenerated via `make rcc`. The build warns, rather than
+//! fails, if the checked in file is missing any of the resources listed in the
+//! qrc file; the `resources` test keeps it in sync.
+//!
+//! The item delegates under `src/delegate`, which must be written in C++ to
+//! subclass Qt's classes, are compiled against the headers of the Qt install
+//! reported by qmake (or the qmake named by the QMAKE environment variable).
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const QRC: &str = "resources/pbgui_tree.qrc";
+const PREBUILT_RCC: &str = "resources/pbgui_tree.rcc";
+const DELEGATES: &[&str] = &["src/delegate/highlight_delegate.cpp"];
+const QT_MODULES: &[&str] = &["QtCore", "QtGui", "QtWidgets"];
+
+// retrieve the paths of the files listed in the qrc file
+fn qrc_files(qrc: &str) -> Vec<String> {
+    qrc.split("<file>")
+        .skip(1)
+        .filter_map(|rest| rest.split("</file>").next())
+        .map(|file| file.trim().to_string())
+        .collect()
+}
+
+// retrieve the files listed in the qrc file which are absent from the rcc
+// file. The rcc file stores the name of each file as UTF-16BE.
+fn missing_from_rcc(files: &[String], rcc: &[u8]) -> Vec<String> {
+    files
+        .iter()
+        .filter(|file| {
+            let name = file.rsplit('/').next().unwrap_or(file.as_str());
+            let encoded = name
+                .encode_utf16()
+                .flat_map(|unit| unit.to_be_bytes().to_vec())
+                .collect::<Vec<_>>();
+            !rcc.windows(encoded.len())
+                .any(|window| window == encoded.as_slice())
+        })
+        .cloned()
+        .collect()
+}
+
+// query a property (eg QT_INSTALL_HEADERS) of the Qt install via qmake
+fn qmake_query(qmake: &str, property: &str) -> String {
+    let output = Command::new(qmake)
+        .arg("-query")
+        .arg(property)
+        .output()
+        .unwrap_or_else(|err| panic!("unable to run {}: {}", qmake, err));
+    assert!(
+        output.status.success(),
+        "{} -query {} failed",
+        qmake,
+        property
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+// compile the item delegates, which are linked against the Qt libraries
+// already linked by the qt crates
+fn compile_delegates() {
+    println!("cargo:rerun-if-env-changed=QMAKE");
+    let qmake = env::var("QMAKE").unwrap_or_else(|_| "qmake".to_string());
+    let headers = PathBuf::from(qmake_query(&qmake, "QT_INSTALL_HEADERS"));
+    let libs = PathBuf::from(qmake_query(&qmake, "QT_INSTALL_LIBS"));
+
+    let mut build = cc::Build::new();
+    build
+        .cpp(true)
+        .flag_if_supported("-std=c++11")
+        .include(&headers);
+    for module in QT_MODULES {
+        build.include(headers.join(module));
+        // Qt is installed as frameworks on macos
+        build.include(libs.join(format!("{}.framework/Headers", module)));
+    }
+    if env::var("CARGO_CFG_TARGET_OS").map_or(false, |os| os == "macos") {
+        build.flag(&format!("-F{}", libs.display()));
+    }
+    for source in DELEGATES {
+        println!("cargo:rerun-if-changed={}", source);
+        build.file(source);
+    }
+    build.compile("pbgui_tree_delegates");
+}
+
+fn main() {
+    compile_delegates();
+
+    println!("cargo:rerun-if-changed={}", QRC);
+    println!("cargo:rerun-if-changed={}", PREBUILT_RCC);
+    println!("cargo:rerun-if-changed=resources/images");
+    println!("cargo:rerun-if-env-changed=RCC");
+
+    let out = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR not set")).join("pbgui_tree.rcc");
+    let rcc = env::var("RCC").unwrap_or_else(|_| "rcc".to_string());
+
+    let compiled = Command::new(&rcc)
+        .arg("-binary")
+        .arg(QRC)
+        .arg("-o")
+        .arg(&out)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false);
+
+    if compiled {
+        return;
+    }
+    let qrc = fs::read_to_string(QRC).expect("unable to read the qrc file");
+    let prebuilt = fs::read(PREBUILT_RCC).expect("unable to read the prebuilt rcc file");
+    let missing = missing_from_rcc(&qrc_files(&qrc), &prebuilt);
+    if !missing.is_empty() {
+        println!(
+            "cargo:warning={} is stale, lacking: {}. Install qt's rcc tool \
+             (or point the RCC environment variable at it), or regenerate {} via `make rcc`",
+            PREBUILT_RCC,
+            missing.join(", "),
+            PREBUILT_RCC
+        );
+    }
+    println!(
+        "cargo:warning=unable to run {} on {}. Embedding {} instead",
+        rcc, QRC, PREBUILT_RCC
+    );
+    fs::copy(PREBUILT_RCC, &out).expect("unable to copy the prebuilt rcc file");
+}
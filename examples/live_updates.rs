@@ -0,0 +1,84 @@
+//! Exercise live updates against a local packrat database.
+//!
+//! The example LISTENs on a private channel, sends a NOTIFY announcing a new
+//! package, and checks that the package has been added to the tree. It exits
+//! with a non zero status if the package fails to arrive within the timeout.
+//!
+//! ```text
+//! cargo run --example live_updates
+//! ```
+use packybara::packrat::{Client, NoTls};
+use pbgui_tree::{live::LiveUpdate, tree};
+use qt_core::{QCoreApplication, QTimer, Slot};
+use qt_gui::QStandardItemModel;
+use qt_widgets::{cpp_core::MutPtr, QApplication};
+
+// the channel LISTENed on, so as not to disturb other listeners
+const CHANNEL: &str = "pbgui_tree_live_example";
+// the package announced by the notification
+const PACKAGE: &str = "live_update_example";
+// how long, in milliseconds, to wait for the notification to arrive
+const TIMEOUT: i32 = 5000;
+
+pub struct ClientProxy {}
+
+impl ClientProxy {
+    pub fn connect() -> Result<Client, Box<dyn std::error::Error>> {
+        let client = Client::connect(
+            "host=127.0.0.1 user=postgres dbname=packrat password=example port=5432",
+            NoTls,
+        )?;
+        Ok(client)
+    }
+}
+
+// determine whether the tree's model has a top level row for the package
+fn has_package(model: MutPtr<QStandardItemModel>, package: &str) -> bool {
+    unsafe {
+        (0..model.row_count_0a()).any(|row| {
+            let item = model.item_1a(row);
+            !item.is_null() && item.text().to_std_string() == package
+        })
+    }
+}
+
+fn main() {
+    QApplication::init(|_app| unsafe {
+        pbgui_tree::init_resources();
+        let mytree = tree::DistributionTreeView::new();
+        mytree.set_packages(vec!["foo", "bar", "bla"]);
+        mytree
+            .start_live_updates_on(CHANNEL)
+            .expect("Unable to listen for live updates");
+
+        let model = mytree.model().expect("Unable to retrieve the model");
+        mytree.live_updated().connect(move |update| {
+            if *update != LiveUpdate::Package(PACKAGE.to_string()) {
+                return;
+            }
+            if has_package(model, PACKAGE) {
+                println!("{} was added to the tree", PACKAGE);
+                QCoreApplication::exit_1a(0);
+            } else {
+                eprintln!("{} was announced but is missing from the tree", PACKAGE);
+                QCoreApplication::exit_1a(1);
+            }
+        });
+
+        let mut client = ClientProxy::connect().expect("Unable to connect via ClientProxy");
+        client
+            .batch_execute(&format!("NOTIFY {}, 'package:{}'", CHANNEL, PACKAGE))
+            .expect("Unable to send the notification");
+
+        let timed_out = Slot::new(|| {
+            eprintln!("Timed out waiting for {} to arrive", PACKAGE);
+            QCoreApplication::exit_1a(1);
+        });
+        let mut timer = QTimer::new_0a();
+        timer.set_single_shot(true);
+        timer.timeout().connect(&timed_out);
+        timer.start_1a(TIMEOUT);
+
+        QApplication::exec()
+    });
+}
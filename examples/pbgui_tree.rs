@@ -2,9 +2,8 @@ use packybara::packrat::PackratDb;
 use packybara::packrat::{Client, NoTls};
 use packybara::traits::*;
 use pbgui_tree::tree;
-use qt_core::QResource;
 use qt_widgets::{QApplication, QFrame, QMainWindow};
-use rustqt_utils::{create_vlayout, qs};
+use rustqt_utils::create_vlayout;
 
 pub struct ClientProxy {}
 
@@ -20,9 +19,7 @@ impl ClientProxy {
 
 fn main() {
     QApplication::init(|_app| unsafe {
-        let _result = QResource::register_resource_q_string(&qs(
-            "/Users/jgerber/src/rust/pbgui-tree/resources/pbgui_tree.rcc",
-        ));
+        pbgui_tree::init_resources();
         let mut main_window = QMainWindow::new_0a();
         let mut main_widget = QFrame::new_0a();
         let main_widget_ptr = main_widget.as_mut_ptr();
@@ -34,12 +31,13 @@ fn main() {
         // set main_widget as the central widget in main_window
         main_window.set_central_widget(main_widget.into_ptr());
 
-        let mytree = tree::DistributionTreeView::create(main_widget_ptr);
+        let mytree = tree::DistributionTreeView::create(main_widget_ptr)
+            .expect("the main widget has a layout");
 
         mytree.set_default_stylesheet();
         mytree.set_packages(vec!["foo", "bar", "bla"]);
 
-        mytree.clear_packages();
+        mytree.clear();
         let client = ClientProxy::connect().expect("Unable to connect via ClientProxy");
         let mut db = PackratDb::new(client);
         let results = get_all_packages(&mut db);
@@ -0,0 +1,774 @@
+use crate::api::{ClientProxy, PackratDb};
+use crate::cache::{CacheStats, TtlCache, DEFAULT_CACHE_TTL};
+use crate::edit::{PinPreview, PinRequest};
+use crate::history::HistoryEntry;
+use crate::loading::LoadingMode;
+use crate::pins::PinLocation;
+use packybara::traits::*;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Error type returned by the backend queries
+pub(crate) type BackendError = Box<dyn std::error::Error>;
+
+// retrieve the versions of the packages in the supplied array
+const VERSIONS_FOR_QUERY: &str =
+    "SELECT package, version FROM distribution_view WHERE package = ANY($1)";
+// count the distributions, without fetching them
+const DISTRIBUTION_COUNT_QUERY: &str = "SELECT COUNT(*) FROM distribution_view";
+// retrieve the pins at a site, along with those at the `any` site, which
+// apply to every site
+const SITE_PINS_QUERY: &str = "SELECT package, version, platform::text AS platform, \
+     role::text AS role, level::text AS level, site::text AS site \
+     FROM versionpin_view WHERE site::text IN ($1, 'any')";
+// as SITE_PINS_QUERY, restricted to the packages in the supplied array
+const SITE_PACKAGE_PINS_QUERY: &str = "SELECT package, version, platform::text AS platform, \
+     role::text AS role, level::text AS level, site::text AS site \
+     FROM versionpin_view WHERE site::text IN ($1, 'any') AND package = ANY($2)";
+
+/// The number of sites, and platforms, at which a version is pinned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct PinCounts {
+    pub(crate) sites: usize,
+    pub(crate) platforms: usize,
+}
+
+// retain the (package, pin) pairs which are in effect at site. As with
+// platforms, pins at the `any` site apply to every site, unless the site has
+// a pin of its own for the package at the same role, level and platform.
+fn effective_pins(pins: Vec<(String, PinLocation)>, site: &str) -> Vec<(String, PinLocation)> {
+    let pinned_at_site = pins
+        .iter()
+        .filter(|(_, pin)| pin.site == site)
+        .map(|(package, pin)| {
+            (
+                package.clone(),
+                pin.role.clone(),
+                pin.level.clone(),
+                pin.platform.clone(),
+            )
+        })
+        .collect::<HashSet<_>>();
+    pins.into_iter()
+        .filter(|(package, pin)| {
+            pin.site == site
+                || (pin.site == "any"
+                    && !pinned_at_site.contains(&(
+                        package.clone(),
+                        pin.role.clone(),
+                        pin.level.clone(),
+                        pin.platform.clone(),
+                    )))
+        })
+        .collect()
+}
+
+// filter using is any
+fn is_not_any(item: &str) -> Option<&str> {
+    if item == "any" {
+        None
+    } else {
+        Some(item)
+    }
+}
+
+/// Backend encapsulates the queries the tree issues against the
+/// packrat database in order to populate its children. Query results
+/// are memoized for the cache's time to live.
+pub(crate) struct Backend {
+    mode: LoadingMode,
+    // map of package name to versions, present when eager loading is in effect
+    index: Option<HashMap<String, Vec<String>>>,
+    // the packages invalidated since the index was built, whose versions are
+    // fetched again before the index is next used
+    stale_index: HashSet<String>,
+    // set when the site has too many distributions to load eagerly
+    fell_back: bool,
+    ttl: Duration,
+    stats: CacheStats,
+    packages: TtlCache<(), Vec<String>>,
+    versions: TtlCache<String, Vec<String>>,
+    platforms: TtlCache<(), Vec<String>>,
+    sites: TtlCache<(), Vec<String>>,
+    pin_counts: TtlCache<String, HashMap<String, PinCounts>>,
+    // keyed by package and site
+    pins: TtlCache<(String, String), Vec<PinLocation>>,
+    // the pins of every package, keyed by site
+    site_pins: TtlCache<String, Vec<(String, PinLocation)>>,
+    // the packages invalidated since the pins of a site were cached, whose
+    // pins are fetched again before the site's pins are next used, keyed by site
+    stale_site_pins: HashMap<String, HashSet<String>>,
+    history: TtlCache<String, Vec<HistoryEntry>>,
+    roles: TtlCache<(), Vec<String>>,
+    levels: TtlCache<(), Vec<String>>,
+}
+
+impl Backend {
+    /// New up a Backend
+    pub(crate) fn new() -> Self {
+        Self {
+            mode: LoadingMode::default(),
+            index: None,
+            stale_index: HashSet::new(),
+            fell_back: false,
+            ttl: DEFAULT_CACHE_TTL,
+            stats: CacheStats::default(),
+            packages: TtlCache::new(),
+            versions: TtlCache::new(),
+            platforms: TtlCache::new(),
+            sites: TtlCache::new(),
+            pin_counts: TtlCache::new(),
+            pins: TtlCache::new(),
+            site_pins: TtlCache::new(),
+            stale_site_pins: HashMap::new(),
+            history: TtlCache::new(),
+            roles: TtlCache::new(),
+            levels: TtlCache::new(),
+        }
+    }
+
+    /// Set the time to live of cached query results. A zero duration
+    /// effectively disables caching.
+    pub(crate) fn set_cache_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+
+    /// Retrieve the time to live of cached query results
+    pub(crate) fn cache_ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Retrieve the cache hit and miss statistics
+    pub(crate) fn cache_stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Reset the cache hit and miss statistics
+    pub(crate) fn reset_cache_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Invalidate the cached versions and pins of a package. The package's
+    /// entries are removed from the eager index, and from the cached pins of
+    /// each site, and only they are fetched again when next required.
+    pub(crate) fn invalidate(&mut self, package: &str) {
+        let key = package.to_string();
+        self.versions.invalidate(&key);
+        self.pin_counts.invalidate(&key);
+        self.pins
+            .invalidate_matching(|(pins_package, _)| pins_package == package);
+        let stale_site_pins = &mut self.stale_site_pins;
+        self.site_pins.update(|site, pins| {
+            pins.retain(|(pins_package, _)| pins_package != package);
+            stale_site_pins
+                .entry(site.clone())
+                .or_default()
+                .insert(key.clone());
+        });
+        if let Some(index) = self.index.as_mut() {
+            index.remove(package);
+            self.stale_index.insert(key);
+        }
+    }
+
+    /// Invalidate the cached package names, so that a new package is
+    /// picked up by the next query
+    pub(crate) fn invalidate_packages(&mut self) {
+        self.packages.clear();
+    }
+
+    /// Invalidate all of the cached query results, along with the eager index
+    pub(crate) fn invalidate_all(&mut self) {
+        self.packages.clear();
+        self.versions.clear();
+        self.platforms.clear();
+        self.sites.clear();
+        self.pin_counts.clear();
+        self.pins.clear();
+        self.site_pins.clear();
+        self.stale_site_pins.clear();
+        self.history.clear();
+        self.roles.clear();
+        self.levels.clear();
+        self.reset_index();
+    }
+
+    /// Set the LoadingMode, discarding any extant index
+    pub(crate) fn set_mode(&mut self, mode: LoadingMode) {
+        self.mode = mode;
+        self.reset_index();
+    }
+
+    /// Retrieve the LoadingMode
+    pub(crate) fn mode(&self) -> LoadingMode {
+        self.mode
+    }
+
+    /// Determine whether expansions are currently served from the in memory index
+    pub(crate) fn is_indexed(&self) -> bool {
+        self.index.is_some()
+    }
+
+    // discard the index, so that it is rebuilt upon the next query
+    fn reset_index(&mut self) {
+        self.index = None;
+        self.stale_index.clear();
+        self.fell_back = false;
+    }
+
+    // build the index of package to versions in a single query, if we are
+    // loading eagerly and have yet to do so
+    fn ensure_index(&mut self) -> Result<(), BackendError> {
+        let max_distributions = match self.mode {
+            LoadingMode::Eager { max_distributions } => max_distributions,
+            LoadingMode::Lazy => return Ok(()),
+        };
+        if self.index.is_some() {
+            return self.patch_index();
+        }
+        if self.fell_back {
+            return Ok(());
+        }
+        // count the distributions before deciding whether to fetch them
+        let mut client = ClientProxy::connect()?;
+        let count = client
+            .query_one(DISTRIBUTION_COUNT_QUERY, &[])?
+            .get::<_, i64>(0) as usize;
+        if count > max_distributions {
+            log::info!(
+                "{} distributions exceeds the eager maximum of {}. Loading lazily",
+                count,
+                max_distributions
+            );
+            self.fell_back = true;
+            return Ok(());
+        }
+        let mut db = PackratDb::new(client);
+        let results = db.find_all_distributions().query()?;
+        let mut index: HashMap<String, Vec<String>> = HashMap::new();
+        for row in results {
+            index.entry(row.package).or_default().push(row.version);
+        }
+        self.index = Some(index);
+        Ok(())
+    }
+
+    // fetch the versions of the packages invalidated since the index was
+    // built, in a single query, replacing their entries in the index
+    fn patch_index(&mut self) -> Result<(), BackendError> {
+        if self.stale_index.is_empty() {
+            return Ok(());
+        }
+        let stale = self.stale_index.iter().cloned().collect::<Vec<_>>();
+        let mut client = ClientProxy::connect()?;
+        let rows = client.query(VERSIONS_FOR_QUERY, &[&stale])?;
+        if let Some(index) = self.index.as_mut() {
+            for row in rows {
+                index
+                    .entry(row.get::<_, String>("package"))
+                    .or_default()
+                    .push(row.get::<_, String>("version"));
+            }
+        }
+        self.stale_index.clear();
+        Ok(())
+    }
+
+    // connect to the database
+    fn db(&self) -> Result<PackratDb, BackendError> {
+        let client = ClientProxy::connect()?;
+        Ok(PackratDb::new(client))
+    }
+
+    /// Retrieve the versions of a package
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    ///
+    /// # Returns
+    /// * Result wrapping a vector of versions, or a BackendError
+    pub(crate) fn versions(&mut self, package: &str) -> Result<Vec<String>, BackendError> {
+        self.ensure_index()?;
+        if let Some(index) = &self.index {
+            return Ok(index.get(package).cloned().unwrap_or_default());
+        }
+        let key = package.to_string();
+        if let Some(versions) = self.versions.get(&key, self.ttl, &mut self.stats) {
+            return Ok(versions);
+        }
+        let mut db = self.db()?;
+        let results = db.find_all_distributions().package(package).query()?;
+        let versions = results
+            .into_iter()
+            .map(|row| row.version)
+            .collect::<Vec<_>>();
+        self.versions.insert(key, versions.clone());
+        Ok(versions)
+    }
+
+    /// Retrieve the versions of a number of packages in a single query
+    ///
+    /// # Arguments
+    /// * `packages` - The names of the packages of interest
+    ///
+    /// # Returns
+    /// * Result wrapping a map of package name to versions, or a BackendError
+    pub(crate) fn versions_for(
+        &mut self,
+        packages: &[String],
+    ) -> Result<HashMap<String, Vec<String>>, BackendError> {
+        self.ensure_index()?;
+        if let Some(index) = &self.index {
+            return Ok(packages
+                .iter()
+                .filter_map(|p| index.get(p).map(|versions| (p.clone(), versions.clone())))
+                .collect());
+        }
+        let mut versions: HashMap<String, Vec<String>> = HashMap::new();
+        let mut wanted = Vec::new();
+        for package in packages {
+            match self.versions.get(package, self.ttl, &mut self.stats) {
+                Some(cached) => {
+                    versions.insert(package.clone(), cached);
+                }
+                None => wanted.push(package.clone()),
+            }
+        }
+        wanted.sort();
+        wanted.dedup();
+        if wanted.is_empty() {
+            return Ok(versions);
+        }
+        // fetch the uncached packages alone, rather than every distribution
+        let mut client = ClientProxy::connect()?;
+        let rows = client.query(VERSIONS_FOR_QUERY, &[&wanted])?;
+        let mut fetched: HashMap<String, Vec<String>> = HashMap::new();
+        for row in rows {
+            fetched
+                .entry(row.get::<_, String>("package"))
+                .or_default()
+                .push(row.get::<_, String>("version"));
+        }
+        for package in wanted {
+            let package_versions = fetched.remove(&package).unwrap_or_default();
+            self.versions
+                .insert(package.clone(), package_versions.clone());
+            if !package_versions.is_empty() {
+                versions.insert(package, package_versions);
+            }
+        }
+        Ok(versions)
+    }
+
+    /// Retrieve the platforms, skipping the `any` platform
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping a vector of platform names, or a BackendError
+    pub(crate) fn platforms(&mut self) -> Result<Vec<String>, BackendError> {
+        if let Some(platforms) = self.platforms.get(&(), self.ttl, &mut self.stats) {
+            return Ok(platforms);
+        }
+        let mut db = self.db()?;
+        let results = db.find_all_platforms().query()?;
+        let platforms = results
+            .iter()
+            .filter_map(|row| is_not_any(row.name.as_str()))
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        self.platforms.insert((), platforms.clone());
+        Ok(platforms)
+    }
+
+    /// Retrieve the names of all of the packages
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping a vector of package names, or a BackendError
+    pub(crate) fn packages(&mut self) -> Result<Vec<String>, BackendError> {
+        if let Some(packages) = self.packages.get(&(), self.ttl, &mut self.stats) {
+            return Ok(packages);
+        }
+        let mut db = self.db()?;
+        let results = db.find_all_packages().query()?;
+        let packages = results.into_iter().map(|row| row.name).collect::<Vec<_>>();
+        self.packages.insert((), packages.clone());
+        Ok(packages)
+    }
+
+    /// Retrieve the names of all of the sites
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping a vector of site names, or a BackendError
+    pub(crate) fn sites(&mut self) -> Result<Vec<String>, BackendError> {
+        if let Some(sites) = self.sites.get(&(), self.ttl, &mut self.stats) {
+            return Ok(sites);
+        }
+        let mut db = self.db()?;
+        let results = db.find_all_sites().query()?;
+        let sites = results.into_iter().map(|row| row.name).collect::<Vec<_>>();
+        self.sites.insert((), sites.clone());
+        Ok(sites)
+    }
+
+    /// Retrieve the number of distinct sites, and platforms, at which each
+    /// version of a package is pinned. A pin on the `any` platform covers
+    /// every platform.
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    ///
+    /// # Returns
+    /// * Result wrapping a map of version to PinCounts, or a BackendError
+    pub(crate) fn pin_counts(
+        &mut self,
+        package: &str,
+    ) -> Result<HashMap<String, PinCounts>, BackendError> {
+        let key = package.to_string();
+        if let Some(counts) = self.pin_counts.get(&key, self.ttl, &mut self.stats) {
+            return Ok(counts);
+        }
+        let all_platforms = self.platforms()?.len();
+        let mut db = self.db()?;
+        let results = db.find_all_versionpins().package(package).query()?;
+        let mut coords: HashMap<String, (HashSet<String>, HashSet<String>)> = HashMap::new();
+        for row in results {
+            let (sites, platforms) = coords
+                .entry(row.distribution.version().to_string())
+                .or_default();
+            sites.insert(row.coords.site.to_string());
+            platforms.insert(row.coords.platform.to_string());
+        }
+        let counts = coords
+            .into_iter()
+            .map(|(version, (sites, platforms))| {
+                let platforms = if platforms.contains("any") {
+                    all_platforms
+                } else {
+                    platforms.len()
+                };
+                (
+                    version,
+                    PinCounts {
+                        sites: sites.len(),
+                        platforms,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        self.pin_counts.insert(key, counts.clone());
+        Ok(counts)
+    }
+
+    /// Retrieve the locations at which the versions of a package are pinned
+    /// for a site, by role, level and platform. Pins at the `any` site apply
+    /// to every site, and are included unless the site overrides them.
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    /// * `site` - The name of the site
+    ///
+    /// # Returns
+    /// * Result wrapping a vector of PinLocations, or a BackendError
+    pub(crate) fn pins(
+        &mut self,
+        package: &str,
+        site: &str,
+    ) -> Result<Vec<PinLocation>, BackendError> {
+        let key = (package.to_string(), site.to_string());
+        if let Some(pins) = self.pins.get(&key, self.ttl, &mut self.stats) {
+            return Ok(pins);
+        }
+        let mut db = self.db()?;
+        let results = db.find_all_versionpins().package(package).query()?;
+        let pins = results
+            .into_iter()
+            .map(|row| {
+                (
+                    package.to_string(),
+                    PinLocation {
+                        version: row.distribution.version().to_string(),
+                        platform: row.coords.platform.to_string(),
+                        role: row.coords.role.to_string(),
+                        level: row.coords.level.to_string(),
+                        site: row.coords.site.to_string(),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let pins = effective_pins(pins, site)
+            .into_iter()
+            .map(|(_, pin)| pin)
+            .collect::<Vec<_>>();
+        self.pins.insert(key, pins.clone());
+        Ok(pins)
+    }
+
+    /// Retrieve the pins of every package at a site, in a single query
+    /// filtered by site. Pins at the `any` site are included unless the site
+    /// overrides them.
+    ///
+    /// # Arguments
+    /// * `site` - The name of the site
+    ///
+    /// # Returns
+    /// * Result wrapping a vector of (package, PinLocation) pairs, or a BackendError
+    pub(crate) fn site_pins(
+        &mut self,
+        site: &str,
+    ) -> Result<Vec<(String, PinLocation)>, BackendError> {
+        let key = site.to_string();
+        if let Some(mut pins) = self.site_pins.get(&key, self.ttl, &mut self.stats) {
+            let stale = match self.stale_site_pins.get(&key) {
+                Some(stale) if !stale.is_empty() => stale.iter().cloned().collect::<Vec<_>>(),
+                _ => return Ok(pins),
+            };
+            // patch the cached pins with those of the invalidated packages
+            let fresh = self.fetch_site_pins(site, Some(&stale))?;
+            self.site_pins.update(|pins_site, cached| {
+                if *pins_site == key {
+                    cached.extend(fresh.iter().cloned());
+                }
+            });
+            self.stale_site_pins.remove(&key);
+            pins.extend(fresh);
+            return Ok(pins);
+        }
+        let pins = self.fetch_site_pins(site, None)?;
+        self.stale_site_pins.remove(&key);
+        self.site_pins.insert(key, pins.clone());
+        Ok(pins)
+    }
+
+    // query the pins in effect at site, of the supplied packages, or of
+    // every package. Only the pins at the site, and at the `any` site, are
+    // fetched.
+    fn fetch_site_pins(
+        &self,
+        site: &str,
+        packages: Option<&[String]>,
+    ) -> Result<Vec<(String, PinLocation)>, BackendError> {
+        let mut client = ClientProxy::connect()?;
+        let rows = match packages {
+            Some(packages) => client.query(SITE_PACKAGE_PINS_QUERY, &[&site, &packages])?,
+            None => client.query(SITE_PINS_QUERY, &[&site])?,
+        };
+        let pins = rows
+            .into_iter()
+            .map(|row| {
+                (
+                    row.get::<_, String>("package"),
+                    PinLocation {
+                        version: row.get::<_, String>("version"),
+                        platform: row.get::<_, String>("platform"),
+                        role: row.get::<_, String>("role"),
+                        level: row.get::<_, String>("level"),
+                        site: row.get::<_, String>("site"),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(effective_pins(pins, site))
+    }
+
+    /// Retrieve the pin history of a package: every change to its pins, along
+    /// with the revision in which the change was made, most recent first
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    ///
+    /// # Returns
+    /// * Result wrapping a vector of HistoryEntries, or a BackendError
+    pub(crate) fn history(&mut self, package: &str) -> Result<Vec<HistoryEntry>, BackendError> {
+        let key = package.to_string();
+        if let Some(history) = self.history.get(&key, self.ttl, &mut self.stats) {
+            return Ok(history);
+        }
+        let mut db = self.db()?;
+        // fetch the changes to the package once, grouped by the transaction
+        // which made them, rather than querying the changes of each revision
+        let mut changes_by_transaction: HashMap<i64, Vec<_>> = HashMap::new();
+        for change in db.find_all_changes().package(package).query()? {
+            changes_by_transaction
+                .entry(change.transaction_id as i64)
+                .or_default()
+                .push(change);
+        }
+        let revisions = db.find_all_revisions().query()?;
+        let mut history = Vec::new();
+        for revision in revisions.into_iter().rev() {
+            let changes = match changes_by_transaction.remove(&(revision.transaction_id as i64)) {
+                Some(changes) => changes,
+                None => continue,
+            };
+            history.extend(changes.into_iter().map(|change| HistoryEntry {
+                transaction_id: revision.transaction_id as i64,
+                datetime: revision.datetime.to_string(),
+                author: revision.author.to_string(),
+                comment: revision.comment.to_string(),
+                action: change.action.to_string(),
+                site: change.site.to_string(),
+                role: change.role.to_string(),
+                level: change.level.to_string(),
+                platform: change.platform.to_string(),
+                version: change.new.version().to_string(),
+                previous_version: match change.old.version().to_string() {
+                    old if old.is_empty() || old == change.new.version() => None,
+                    old => Some(old),
+                },
+            }));
+        }
+        self.history.insert(key, history.clone());
+        Ok(history)
+    }
+
+    /// Retrieve the names of all of the roles
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping a vector of role names, or a BackendError
+    pub(crate) fn roles(&mut self) -> Result<Vec<String>, BackendError> {
+        if let Some(roles) = self.roles.get(&(), self.ttl, &mut self.stats) {
+            return Ok(roles);
+        }
+        let mut db = self.db()?;
+        let results = db.find_all_roles().query()?;
+        let roles = results
+            .into_iter()
+            .map(|row| row.role.to_string())
+            .collect::<Vec<_>>();
+        self.roles.insert((), roles.clone());
+        Ok(roles)
+    }
+
+    /// Retrieve the names of all of the levels
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping a vector of level names, or a BackendError
+    pub(crate) fn levels(&mut self) -> Result<Vec<String>, BackendError> {
+        if let Some(levels) = self.levels.get(&(), self.ttl, &mut self.stats) {
+            return Ok(levels);
+        }
+        let mut db = self.db()?;
+        let results = db.find_all_levels().query()?;
+        let levels = results
+            .into_iter()
+            .map(|row| row.level.to_string())
+            .collect::<Vec<_>>();
+        self.levels.insert((), levels.clone());
+        Ok(levels)
+    }
+
+    /// Validate a pin request and preview its outcome, without writing anything
+    ///
+    /// # Arguments
+    /// * `request` - The PinRequest
+    ///
+    /// # Returns
+    /// * Result wrapping the PinPreview, or a BackendError if the request is invalid
+    pub(crate) fn preview_pin(&mut self, request: &PinRequest) -> Result<PinPreview, BackendError> {
+        let roles = self.roles()?;
+        let levels = self.levels()?;
+        let platforms = self.platforms()?;
+        request.validate(&roles, &levels, &platforms)?;
+        if !self.versions(&request.package)?.contains(&request.version) {
+            return Err(format!(
+                "{}-{} is not a known distribution",
+                request.package, request.version
+            )
+            .into());
+        }
+        let replaces = self
+            .pins(&request.package, &request.site)?
+            .into_iter()
+            .find(|pin| request.targets(pin))
+            .map(|pin| pin.version);
+        Ok(PinPreview {
+            request: request.clone(),
+            replaces,
+        })
+    }
+
+    /// Write the version pin described by the request within a transaction,
+    /// invalidating the cached results of the package. A pin which replaces
+    /// the version pinned at the request's coordinates updates that pin, as
+    /// the coordinates may only be pinned once.
+    ///
+    /// # Arguments
+    /// * `request` - The PinRequest
+    /// * `replaces` - Whether a version is already pinned at the request's coordinates
+    /// * `author` - The user recorded as having made the change
+    /// * `comment` - The comment recorded with the change
+    ///
+    /// # Returns
+    /// * Ok(()) or a BackendError
+    pub(crate) fn create_pin(
+        &mut self,
+        request: &PinRequest,
+        replaces: bool,
+        author: &str,
+        comment: &str,
+    ) -> Result<(), BackendError> {
+        let mut db = self.db()?;
+        let tx = db.transaction();
+        let results = if replaces {
+            PackratDb::update_versionpins(tx, &request.package, &request.version)
+                .platforms(vec![request.platform.clone()])
+                .roles(vec![request.role.clone()])
+                .levels(vec![request.level.clone()])
+                .sites(vec![request.site.clone()])
+                .update()?
+        } else {
+            PackratDb::add_versionpins(tx, &request.package, &request.version)
+                .platforms(vec![request.platform.clone()])
+                .roles(vec![request.role.clone()])
+                .levels(vec![request.level.clone()])
+                .sites(vec![request.site.clone()])
+                .create()?
+        };
+        results.commit(author, comment)?;
+        self.invalidate(&request.package);
+        Ok(())
+    }
+
+    /// Remove the version pin described by the request within a transaction,
+    /// invalidating the cached results of the package.
+    ///
+    /// # Arguments
+    /// * `request` - The PinRequest describing the pin to remove
+    /// * `author` - The user recorded as having made the change
+    /// * `comment` - The comment recorded with the change
+    ///
+    /// # Returns
+    /// * Ok(()) or a BackendError
+    pub(crate) fn remove_pin(
+        &mut self,
+        request: &PinRequest,
+        author: &str,
+        comment: &str,
+    ) -> Result<(), BackendError> {
+        let mut db = self.db()?;
+        let tx = db.transaction();
+        let results = PackratDb::delete_versionpins(tx, &request.package, &request.version)
+            .platforms(vec![request.platform.clone()])
+            .roles(vec![request.role.clone()])
+            .levels(vec![request.level.clone()])
+            .sites(vec![request.site.clone()])
+            .delete()?;
+        results.commit(author, comment)?;
+        self.invalidate(&request.package);
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+use crate::columns::Column;
+use crate::filter::FilterMode;
+use crate::grouping::Grouping;
+use crate::hierarchy::{Hierarchy, HierarchyError};
+use crate::loading::LoadingMode;
+use crate::selection::SelectionMode;
+use crate::theme::{Theme, ThemeError};
+use crate::tree::DistributionTreeView;
+use crate::widget::WidgetError;
+use qt_widgets::{
+    cpp_core::{MutPtr, StaticUpcast},
+    QWidget,
+};
+use std::fmt;
+
+/// Where the tree retrieves its packages and sites from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataSource {
+    /// Query the packages and sites from the packrat database (the default)
+    Database,
+    /// Display the supplied packages and sites
+    Static {
+        packages: Vec<String>,
+        sites: Vec<String>,
+    },
+}
+
+impl Default for DataSource {
+    fn default() -> Self {
+        DataSource::Database
+    }
+}
+
+/// Errors raised when validating or building a DistributionTreeView
+#[derive(Debug)]
+pub enum BuildError {
+    /// The parent could not host the tree
+    Widget(WidgetError),
+    /// The static data source does not supply any sites
+    NoSites,
+    /// The initial site is not one of the available sites
+    UnknownSite(String),
+    /// A column appears more than once in the column set
+    DuplicateColumn(Column),
+    /// The maximum number of visible sites must be positive
+    InvalidMaxVisibleItems(i32),
+    /// The theme's stylesheet could not be read
+    Theme(std::io::Error),
+    /// The packages or sites could not be queried from the database
+    Backend(String),
+    /// The hierarchy cannot accommodate the configuration (eg grouping a
+    /// hierarchy which does not start with packages)
+    Hierarchy(HierarchyError),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::Widget(err) => write!(f, "{}", err),
+            BuildError::NoSites => write!(f, "the data source does not supply any sites"),
+            BuildError::UnknownSite(site) => {
+                write!(f, "{} is not one of the available sites", site)
+            }
+            BuildError::DuplicateColumn(column) => {
+                write!(f, "the {} column is listed more than once", column.title())
+            }
+            BuildError::InvalidMaxVisibleItems(max) => {
+                write!(
+                    f,
+                    "the maximum number of visible sites must be positive, not {}",
+                    max
+                )
+            }
+            BuildError::Theme(err) => write!(f, "unable to read the theme: {}", err),
+            BuildError::Backend(err) => write!(f, "unable to query the database: {}", err),
+            BuildError::Hierarchy(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl From<WidgetError> for BuildError {
+    fn from(err: WidgetError) -> Self {
+        BuildError::Widget(err)
+    }
+}
+
+impl From<ThemeError> for BuildError {
+    fn from(err: ThemeError) -> Self {
+        match err {
+            ThemeError::Io(err) => BuildError::Theme(err),
+            ThemeError::Widget(err) => BuildError::Widget(err),
+        }
+    }
+}
+
+/// Configures a DistributionTreeView up front, validating the configuration
+/// before producing a fully wired widget.
+///
+/// ```ignore
+/// let tree = DistributionTreeViewBuilder::new()
+///     .site("portland")
+///     .theme(Theme::Light)
+///     .columns(vec![Column::LatestVersion, Column::VersionCount])
+///     .selection_mode(SelectionMode::Extended)
+///     .build(main_widget_ptr)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct DistributionTreeViewBuilder {
+    data_source: DataSource,
+    site: Option<String>,
+    theme: Theme,
+    filter_mode: FilterMode,
+    filter_visible: bool,
+    columns: Option<Vec<Column>>,
+    selection_mode: SelectionMode,
+    loading_mode: LoadingMode,
+    max_visible_items: Option<i32>,
+    hierarchy: Hierarchy,
+    grouping: Option<Grouping>,
+}
+
+impl Default for DistributionTreeViewBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DistributionTreeViewBuilder {
+    /// New up a builder with the default configuration: packages and sites
+    /// from the database, the first site, the dark theme, a hidden
+    /// substring filter, a single column, single selection and lazy loading.
+    pub fn new() -> Self {
+        Self {
+            data_source: DataSource::default(),
+            site: None,
+            theme: Theme::default(),
+            filter_mode: FilterMode::default(),
+            filter_visible: false,
+            columns: None,
+            selection_mode: SelectionMode::default(),
+            loading_mode: LoadingMode::default(),
+            max_visible_items: None,
+            hierarchy: Hierarchy::default(),
+            grouping: None,
+        }
+    }
+
+    /// Set where the packages and sites are retrieved from
+    pub fn data_source(mut self, data_source: DataSource) -> Self {
+        self.data_source = data_source;
+        self
+    }
+
+    /// Set the initial site. It must be one of the available sites. The first
+    /// site is selected by default.
+    pub fn site<S: Into<String>>(mut self, site: S) -> Self {
+        self.site = Some(site.into());
+        self
+    }
+
+    /// Set the theme
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Set how the package filter matches package names
+    pub fn filter_mode(mut self, mode: FilterMode) -> Self {
+        self.filter_mode = mode;
+        self
+    }
+
+    /// Set whether the package filter is initially visible
+    pub fn filter_visible(mut self, visible: bool) -> Self {
+        self.filter_visible = visible;
+        self
+    }
+
+    /// Display the tree in multi column mode, showing the supplied optional
+    /// columns. An empty set shows only the name and pin columns.
+    pub fn columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = Some(columns);
+        self
+    }
+
+    /// Set the selection mode
+    pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.selection_mode = mode;
+        self
+    }
+
+    /// Set whether versions are loaded lazily or eagerly
+    pub fn loading_mode(mut self, mode: LoadingMode) -> Self {
+        self.loading_mode = mode;
+        self
+    }
+
+    /// Set the maximum number of sites visible in the site combobox at once
+    pub fn max_visible_items(mut self, max: i32) -> Self {
+        self.max_visible_items = Some(max);
+        self
+    }
+
+    /// Set the hierarchy describing the levels of the tree. With a static
+    /// data source, the packages are used as the top level items.
+    pub fn hierarchy(mut self, hierarchy: Hierarchy) -> Self {
+        self.hierarchy = hierarchy;
+        self
+    }
+
+    /// Bucket the top level packages into collapsible groups
+    pub fn grouping(mut self, grouping: Grouping) -> Self {
+        self.grouping = Some(grouping);
+        self
+    }
+
+    /// Validate the parts of the configuration which do not require the database
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Ok(()) or the first BuildError encountered
+    pub fn validate(&self) -> Result<(), BuildError> {
+        if let DataSource::Static { sites, .. } = &self.data_source {
+            if sites.is_empty() {
+                return Err(BuildError::NoSites);
+            }
+            if let Some(site) = &self.site {
+                if !sites.contains(site) {
+                    return Err(BuildError::UnknownSite(site.clone()));
+                }
+            }
+        }
+        if let Some(columns) = &self.columns {
+            for (idx, column) in columns.iter().enumerate() {
+                if columns[..idx].contains(column) {
+                    return Err(BuildError::DuplicateColumn(*column));
+                }
+            }
+        }
+        if let Some(max) = self.max_visible_items {
+            if max < 1 {
+                return Err(BuildError::InvalidMaxVisibleItems(max));
+            }
+        }
+        if self.grouping.is_some() {
+            self.hierarchy.grouped().map_err(BuildError::Hierarchy)?;
+        }
+        self.theme.stylesheet().map_err(BuildError::Theme)?;
+        Ok(())
+    }
+
+    /// Validate the configuration and build the tree, adding it to the
+    /// layout of the supplied parent
+    ///
+    /// # Arguments
+    /// * `parent_widget` - The widget whose layout the tree is added to
+    ///
+    /// # Returns
+    /// * Result wrapping the DistributionTreeView, or a BuildError
+    pub fn build<'a, T>(
+        self,
+        parent_widget: MutPtr<T>,
+    ) -> Result<DistributionTreeView<'a>, BuildError>
+    where
+        T: StaticUpcast<QWidget>,
+    {
+        unsafe {
+            let parent: MutPtr<QWidget> = parent_widget.static_upcast_mut();
+            if parent.layout().is_null() {
+                return Err(BuildError::Widget(WidgetError::MissingLayout));
+            }
+        }
+        let tree = self.build_unparented()?;
+        let widget = tree.widget()?;
+        unsafe {
+            let parent: MutPtr<QWidget> = parent_widget.static_upcast_mut();
+            parent.layout().add_widget(widget);
+        }
+        Ok(tree)
+    }
+
+    /// Validate the configuration and build the tree without a parent. Place
+    /// it via `DistributionTreeView::widget`.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping the DistributionTreeView, or a BuildError
+    pub fn build_unparented<'a>(self) -> Result<DistributionTreeView<'a>, BuildError> {
+        self.validate()?;
+        let tree = DistributionTreeView::new();
+        tree.set_theme(self.theme)?;
+        tree.set_selection_mode(self.selection_mode);
+        tree.set_filter_mode(self.filter_mode);
+        tree.set_hierarchy(self.hierarchy);
+        if let Some(grouping) = self.grouping.clone() {
+            // without packages, grouping does not query the database
+            tree.set_grouping(Some(grouping))
+                .map_err(|err| BuildError::Backend(err.to_string()))?;
+        }
+        if let Some(columns) = &self.columns {
+            // multi column mode clears the packages, so it precedes them
+            tree.set_multi_column(true);
+            for column in Column::ALL.iter() {
+                tree.set_column_visible(*column, columns.contains(column));
+            }
+        }
+        if let Some(max) = self.max_visible_items {
+            tree.set_cb_max_visible_items(max);
+        }
+        match self.data_source {
+            DataSource::Database => {
+                tree.load_sites(self.site.as_deref())
+                    .map_err(|err| BuildError::Backend(err.to_string()))?;
+                if tree.sites().is_empty() {
+                    return Err(BuildError::NoSites);
+                }
+                if let Some(site) = self.site {
+                    if tree.current_site() != site {
+                        return Err(BuildError::UnknownSite(site));
+                    }
+                }
+                tree.load_packages()
+                    .map_err(|err| BuildError::Backend(err.to_string()))?;
+            }
+            DataSource::Static { packages, sites } => {
+                let site = self.site.unwrap_or_else(|| sites[0].clone());
+                tree.set_sites(sites.iter().map(|s| s.as_str()).collect(), site.as_str());
+                tree.set_packages(packages);
+            }
+        }
+        // the loading mode applies to the queries made once the data is loaded
+        tree.set_loading_mode(self.loading_mode);
+        unsafe {
+            tree.filter_check_box()?.set_checked(self.filter_visible);
+        }
+        Ok(tree)
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// The default time to live of cached query results
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Hit and miss statistics for the query cache, useful when tuning
+/// the time to live.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Retrieve the ratio of hits to lookups, or 0.0 if there have been no lookups
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// A map whose entries expire once they are older than the time to live
+pub(crate) struct TtlCache<K, V> {
+    entries: HashMap<K, (Instant, V)>,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    /// New up an empty TtlCache
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Retrieve a clone of the value stored under key, if it exists and is
+    /// younger than ttl, recording the hit or miss in stats.
+    pub(crate) fn get(&mut self, key: &K, ttl: Duration, stats: &mut CacheStats) -> Option<V> {
+        let fresh = match self.entries.get(key) {
+            Some((inserted, _)) => inserted.elapsed() < ttl,
+            None => false,
+        };
+        if fresh {
+            stats.hits += 1;
+            self.entries.get(key).map(|(_, value)| value.clone())
+        } else {
+            stats.misses += 1;
+            self.entries.remove(key);
+            None
+        }
+    }
+
+    /// Store a value under key
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        self.entries.insert(key, (Instant::now(), value));
+    }
+
+    /// Remove the value stored under key
+    pub(crate) fn invalidate(&mut self, key: &K) {
+        self.entries.remove(key);
+    }
+
+    /// Remove the values whose keys match the predicate
+    pub(crate) fn invalidate_matching<F>(&mut self, predicate: F)
+    where
+        F: Fn(&K) -> bool,
+    {
+        self.entries.retain(|key, _| !predicate(key));
+    }
+
+    /// Modify the values in place, without renewing them
+    pub(crate) fn update<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V),
+    {
+        for (key, (_, value)) in self.entries.iter_mut() {
+            f(key, value);
+        }
+    }
+
+    /// Remove all values
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn get_records_hits_and_misses() {
+        let mut cache = TtlCache::new();
+        let mut stats = CacheStats::default();
+        assert_eq!(cache.get(&"maya", TTL, &mut stats), None);
+        cache.insert("maya", vec!["2020.1"]);
+        assert_eq!(cache.get(&"maya", TTL, &mut stats), Some(vec!["2020.1"]));
+        assert_eq!(cache.get(&"maya", TTL, &mut stats), Some(vec!["2020.1"]));
+        assert_eq!(stats, CacheStats { hits: 2, misses: 1 });
+    }
+
+    #[test]
+    fn entries_expire_once_older_than_the_ttl() {
+        let mut cache = TtlCache::new();
+        let mut stats = CacheStats::default();
+        cache.insert("maya", 1);
+        assert_eq!(cache.get(&"maya", Duration::from_secs(0), &mut stats), None);
+        // the expired entry is discarded
+        assert_eq!(cache.get(&"maya", TTL, &mut stats), None);
+        assert_eq!(stats, CacheStats { hits: 0, misses: 2 });
+    }
+
+    #[test]
+    fn invalidation_removes_entries() {
+        let mut cache = TtlCache::new();
+        let mut stats = CacheStats::default();
+        for key in &["maya", "mari", "nuke"] {
+            cache.insert(*key, 1);
+        }
+        cache.invalidate(&"nuke");
+        assert_eq!(cache.get(&"nuke", TTL, &mut stats), None);
+        cache.invalidate_matching(|key| key.starts_with("ma"));
+        assert_eq!(cache.get(&"maya", TTL, &mut stats), None);
+        assert_eq!(cache.get(&"mari", TTL, &mut stats), None);
+        cache.insert("maya", 1);
+        cache.update(|_, value| *value += 1);
+        assert_eq!(cache.get(&"maya", TTL, &mut stats), Some(2));
+        cache.clear();
+        assert_eq!(cache.get(&"maya", TTL, &mut stats), None);
+    }
+
+    #[test]
+    fn hit_ratio_is_the_share_of_lookups_which_hit() {
+        assert_eq!(CacheStats::default().hit_ratio(), 0.0);
+        assert_eq!(CacheStats { hits: 3, misses: 1 }.hit_ratio(), 0.75);
+    }
+}
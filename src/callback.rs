@@ -0,0 +1,53 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A list of rust closures which may be registered against, and invoked by,
+/// the tree. Qt signals may not carry arbitrary rust types, so signals whose
+/// payload is a rust type are modeled with a Callbacks instance instead.
+pub struct Callbacks<T> {
+    callbacks: RefCell<Vec<Rc<dyn Fn(&T)>>>,
+}
+
+impl<T> Callbacks<T> {
+    /// New up an empty list of callbacks
+    pub(crate) fn new() -> Self {
+        Self {
+            callbacks: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Register a closure to be invoked whenever the callbacks are emitted
+    ///
+    /// # Arguments
+    /// * `callback` - A closure taking a reference to the payload
+    ///
+    /// # Returns
+    /// * None
+    pub fn connect<F>(&self, callback: F)
+    where
+        F: Fn(&T) + 'static,
+    {
+        self.callbacks.borrow_mut().push(Rc::new(callback));
+    }
+
+    /// Remove all registered closures
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn disconnect_all(&self) {
+        self.callbacks.borrow_mut().clear();
+    }
+
+    /// Invoke each registered closure with the supplied value. The closures
+    /// are invoked from a copy of the list, so that they may themselves
+    /// connect, disconnect or emit.
+    pub(crate) fn emit(&self, value: &T) {
+        let callbacks = self.callbacks.borrow().clone();
+        for callback in callbacks.iter() {
+            callback(value);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::cmp::Ordering;
+
+/// The optional columns displayed in multi column mode. Package rows fill in
+/// the latest version and version count, while version rows fill in the
+/// number of sites, and platforms, at which the version is pinned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    LatestVersion,
+    VersionCount,
+    PinnedSiteCount,
+    PlatformCount,
+}
+
+impl Column {
+    /// All of the optional columns, in display order
+    pub const ALL: [Column; 4] = [
+        Column::LatestVersion,
+        Column::VersionCount,
+        Column::PinnedSiteCount,
+        Column::PlatformCount,
+    ];
+
+    /// Retrieve the model column of the Column. Column 0 is always the name,
+    /// and the pin column is always last.
+    pub fn index(&self) -> i32 {
+        match self {
+            Column::LatestVersion => 1,
+            Column::VersionCount => 2,
+            Column::PinnedSiteCount => 3,
+            Column::PlatformCount => 4,
+        }
+    }
+
+    /// Retrieve the title displayed in the header
+    pub fn title(&self) -> &'static str {
+        match self {
+            Column::LatestVersion => "Latest",
+            Column::VersionCount => "Versions",
+            Column::PinnedSiteCount => "Pinned Sites",
+            Column::PlatformCount => "Platforms",
+        }
+    }
+}
+
+/// Compare two versions component by component, comparing numeric
+/// components numerically (so that 2020.10 > 2020.9).
+///
+/// # Arguments
+/// * `left` - The first version
+/// * `right` - The second version
+///
+/// # Returns
+/// * Ordering
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+    let mut left_pieces = left.split(|c| c == '.' || c == '-' || c == '_');
+    let mut right_pieces = right.split(|c| c == '.' || c == '-' || c == '_');
+    loop {
+        match (left_pieces.next(), right_pieces.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) => {
+                let ordering = match (l.parse::<u64>(), r.parse::<u64>()) {
+                    (Ok(l), Ok(r)) => l.cmp(&r),
+                    _ => l.cmp(r),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// Retrieve the latest of the supplied versions, if any
+pub fn latest_version<'a, I>(versions: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    versions
+        .into_iter()
+        .map(|v| v.as_str())
+        .max_by(|l, r| compare_versions(l, r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_versions_compares_numeric_components_numerically() {
+        assert_eq!(compare_versions("2020.10", "2020.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.2-3", "1.2_3"), Ordering::Equal);
+        assert_eq!(compare_versions("1.beta", "1.alpha"), Ordering::Greater);
+    }
+
+    #[test]
+    fn latest_version_is_the_greatest() {
+        let versions = [
+            "2020.9".to_string(),
+            "2020.10".to_string(),
+            "2019.1".to_string(),
+        ];
+        assert_eq!(latest_version(versions.iter()), Some("2020.10"));
+        assert_eq!(latest_version(Vec::<String>::new().iter()), None);
+    }
+}
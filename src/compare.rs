@@ -0,0 +1,310 @@
+use crate::pins::PinLocation;
+use std::collections::{BTreeMap, HashMap};
+
+/// The titles of the columns of the side by side comparison preceding the
+/// versions at each site, which are titled by the names of the sites
+pub const COMPARISON_COLUMNS: [&str; 3] = ["Package", "Pin", "Platform"];
+
+/// The coordinates of a pin, independent of the site and version
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PinCoord {
+    pub role: String,
+    pub level: String,
+    pub platform: String,
+}
+
+impl PinCoord {
+    fn from_pin(pin: &PinLocation) -> Self {
+        Self {
+            role: pin.role.clone(),
+            level: pin.level.clone(),
+            platform: pin.platform.clone(),
+        }
+    }
+}
+
+/// A difference between the pins of a package at two sites
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinDifference {
+    /// The coordinate is only pinned at the first site
+    OnlyInFirst(PinLocation),
+    /// The coordinate is only pinned at the second site
+    OnlyInSecond(PinLocation),
+    /// The coordinate is pinned to different versions at each site
+    Differs {
+        first: PinLocation,
+        second: PinLocation,
+    },
+}
+
+impl PinDifference {
+    /// Determine whether the difference involves the supplied version
+    pub fn involves(&self, version: &str) -> bool {
+        match self {
+            PinDifference::OnlyInFirst(pin) | PinDifference::OnlyInSecond(pin) => {
+                pin.version == version
+            }
+            PinDifference::Differs { first, second } => {
+                first.version == version || second.version == version
+            }
+        }
+    }
+
+    /// Retrieve the versions pinned at the first and second sites, if any
+    pub fn versions(&self) -> (Option<&str>, Option<&str>) {
+        match self {
+            PinDifference::OnlyInFirst(pin) => (Some(pin.version.as_str()), None),
+            PinDifference::OnlyInSecond(pin) => (None, Some(pin.version.as_str())),
+            PinDifference::Differs { first, second } => {
+                (Some(first.version.as_str()), Some(second.version.as_str()))
+            }
+        }
+    }
+
+    // the pin supplying the coordinates of the difference, which are shared
+    // by both sides
+    fn pin(&self) -> &PinLocation {
+        match self {
+            PinDifference::OnlyInFirst(pin) | PinDifference::OnlyInSecond(pin) => pin,
+            PinDifference::Differs { first, .. } => first,
+        }
+    }
+
+    /// Describe the difference (eg `lighting @ dev01 (cent7_64): 2020.1 at portland, 2020.2 at playa`)
+    pub fn describe(&self) -> String {
+        match self {
+            PinDifference::OnlyInFirst(pin) | PinDifference::OnlyInSecond(pin) => format!(
+                "{} ({}): {} only at {}",
+                pin.label(),
+                pin.platform,
+                pin.version,
+                pin.site
+            ),
+            PinDifference::Differs { first, second } => format!(
+                "{} ({}): {} at {}, {} at {}",
+                first.label(),
+                first.platform,
+                first.version,
+                first.site,
+                second.version,
+                second.site
+            ),
+        }
+    }
+}
+
+/// The result of comparing the pins of two sites
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteComparison {
+    pub first_site: String,
+    pub second_site: String,
+    /// The differences, keyed by package. Packages which do not differ are absent.
+    pub packages: BTreeMap<String, Vec<PinDifference>>,
+}
+
+impl SiteComparison {
+    /// Compare the pins of two sites
+    ///
+    /// # Arguments
+    /// * `first_site` - The name of the first site
+    /// * `first` - The pins of the first site, as (package, PinLocation) pairs
+    /// * `second_site` - The name of the second site
+    /// * `second` - The pins of the second site, as (package, PinLocation) pairs
+    ///
+    /// # Returns
+    /// * SiteComparison instance
+    pub fn new(
+        first_site: &str,
+        first: &[(String, PinLocation)],
+        second_site: &str,
+        second: &[(String, PinLocation)],
+    ) -> Self {
+        fn by_coord(pins: &[(String, PinLocation)]) -> HashMap<(&str, PinCoord), &PinLocation> {
+            pins.iter()
+                .map(|(package, pin)| ((package.as_str(), PinCoord::from_pin(pin)), pin))
+                .collect()
+        }
+        let first_pins = by_coord(first);
+        let second_pins = by_coord(second);
+        let mut packages: BTreeMap<String, Vec<PinDifference>> = BTreeMap::new();
+
+        for (key, first_pin) in first_pins.iter() {
+            let difference = match second_pins.get(key) {
+                Some(second_pin) if second_pin.version == first_pin.version => continue,
+                Some(second_pin) => PinDifference::Differs {
+                    first: (*first_pin).clone(),
+                    second: (*second_pin).clone(),
+                },
+                None => PinDifference::OnlyInFirst((*first_pin).clone()),
+            };
+            packages
+                .entry(key.0.to_string())
+                .or_default()
+                .push(difference);
+        }
+        for (key, second_pin) in second_pins.iter() {
+            if !first_pins.contains_key(key) {
+                packages
+                    .entry(key.0.to_string())
+                    .or_default()
+                    .push(PinDifference::OnlyInSecond((*second_pin).clone()));
+            }
+        }
+        for differences in packages.values_mut() {
+            differences.sort_by_key(|difference| difference.describe());
+        }
+        Self {
+            first_site: first_site.to_string(),
+            second_site: second_site.to_string(),
+            packages,
+        }
+    }
+
+    /// Determine whether the pins of a package differ between the sites
+    pub fn differs(&self, package: &str) -> bool {
+        self.packages.contains_key(package)
+    }
+
+    /// Retrieve the differences for a package
+    pub fn differences(&self, package: &str) -> &[PinDifference] {
+        self.packages
+            .get(package)
+            .map(|differences| differences.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Retrieve the titles of the columns of the side by side comparison:
+    /// COMPARISON_COLUMNS, followed by the names of the sites
+    pub fn columns(&self) -> [String; 5] {
+        [
+            COMPARISON_COLUMNS[0].to_string(),
+            COMPARISON_COLUMNS[1].to_string(),
+            COMPARISON_COLUMNS[2].to_string(),
+            self.first_site.clone(),
+            self.second_site.clone(),
+        ]
+    }
+
+    /// Retrieve the rows of the side by side comparison, one per difference,
+    /// ordered by package. Each row holds the package, the pin and platform,
+    /// and the versions pinned at the first and second sites, which are empty
+    /// where the site lacks the pin.
+    pub fn rows(&self) -> Vec<[String; 5]> {
+        self.packages
+            .iter()
+            .flat_map(|(package, differences)| {
+                differences.iter().map(move |difference| {
+                    let pin = difference.pin();
+                    let (first, second) = difference.versions();
+                    [
+                        package.clone(),
+                        pin.label(),
+                        pin.platform.clone(),
+                        first.unwrap_or("").to_string(),
+                        second.unwrap_or("").to_string(),
+                    ]
+                })
+            })
+            .collect()
+    }
+
+    /// Summarize the differences for a package (eg `2 only at portland, 1 only at playa, 3 differ`)
+    pub fn summary(&self, package: &str) -> String {
+        let differences = self.differences(package);
+        let count = |f: fn(&PinDifference) -> bool| differences.iter().filter(|d| f(d)).count();
+        format!(
+            "{} only at {}, {} only at {}, {} differ",
+            count(|d| matches!(d, PinDifference::OnlyInFirst(_))),
+            self.first_site,
+            count(|d| matches!(d, PinDifference::OnlyInSecond(_))),
+            self.second_site,
+            count(|d| matches!(d, PinDifference::Differs { .. })),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(site: &str, role: &str, version: &str) -> (String, PinLocation) {
+        (
+            "maya".to_string(),
+            PinLocation {
+                version: version.to_string(),
+                platform: "cent7_64".to_string(),
+                role: role.to_string(),
+                level: "facility".to_string(),
+                site: site.to_string(),
+            },
+        )
+    }
+
+    fn comparison() -> SiteComparison {
+        let first = vec![
+            pin("portland", "any", "2020.1"),
+            pin("portland", "lighting", "2020.1"),
+            pin("portland", "model", "2019.1"),
+        ];
+        let second = vec![
+            pin("playa", "any", "2020.1"),
+            pin("playa", "lighting", "2020.2"),
+            pin("playa", "fx", "2018.1"),
+        ];
+        SiteComparison::new("portland", &first, "playa", &second)
+    }
+
+    #[test]
+    fn new_records_only_the_differences() {
+        let comparison = comparison();
+        assert!(comparison.differs("maya"));
+        assert!(!comparison.differs("nuke"));
+        let differences = comparison.differences("maya");
+        assert_eq!(differences.len(), 3);
+        assert!(differences.iter().any(|d| match d {
+            PinDifference::Differs { first, second } => {
+                first.version == "2020.1" && second.version == "2020.2"
+            }
+            _ => false,
+        }));
+        assert!(differences
+            .iter()
+            .any(|d| matches!(d, PinDifference::OnlyInFirst(pin) if pin.role == "model")));
+        assert!(differences
+            .iter()
+            .any(|d| matches!(d, PinDifference::OnlyInSecond(pin) if pin.role == "fx")));
+        assert!(differences.iter().any(|d| d.involves("2020.2")));
+        assert!(!differences.iter().any(|d| d.involves("2017.1")));
+    }
+
+    #[test]
+    fn summary_counts_each_kind_of_difference() {
+        assert_eq!(
+            comparison().summary("maya"),
+            "1 only at portland, 1 only at playa, 1 differ"
+        );
+    }
+
+    #[test]
+    fn rows_list_the_versions_side_by_side() {
+        let comparison = comparison();
+        assert_eq!(comparison.columns()[3], "portland");
+        assert_eq!(comparison.columns()[4], "playa");
+        let rows = comparison.rows();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.contains(&[
+            "maya".to_string(),
+            "lighting @ facility".to_string(),
+            "cent7_64".to_string(),
+            "2020.1".to_string(),
+            "2020.2".to_string(),
+        ]));
+        assert!(rows.contains(&[
+            "maya".to_string(),
+            "fx @ facility".to_string(),
+            "cent7_64".to_string(),
+            String::new(),
+            "2018.1".to_string(),
+        ]));
+    }
+}
@@ -0,0 +1,32 @@
+//! Item delegates implemented in C++, as Qt's classes cannot be subclassed
+//! from rust. The sources, under `src/delegate`, are compiled by the build
+//! script.
+use qt_core::QObject;
+use qt_widgets::{cpp_core::MutPtr, QStyledItemDelegate};
+
+extern "C" {
+    fn pbgui_tree_highlight_delegate_new(
+        role: i32,
+        parent: *mut QObject,
+    ) -> *mut QStyledItemDelegate;
+}
+
+/// Create a delegate which draws the rich text stored under the supplied
+/// data role in place of an item's text. Items without any are drawn as
+/// usual.
+///
+/// # Arguments
+/// * `role` - The data role holding the rich text
+/// * `parent` - The QObject which takes ownership of the delegate
+///
+/// # Returns
+/// * MutPtr to the QStyledItemDelegate
+pub(crate) unsafe fn highlight_delegate(
+    role: i32,
+    parent: MutPtr<QObject>,
+) -> MutPtr<QStyledItemDelegate> {
+    MutPtr::from_raw(pbgui_tree_highlight_delegate_new(
+        role,
+        parent.as_mut_raw_ptr(),
+    ))
+}
@@ -0,0 +1,75 @@
+// An item delegate which draws the rich text stored under a data role in
+// place of an item's text, so that the matches of the filter may be
+// highlighted. Items without rich text are drawn as usual. Qt's classes
+// cannot be subclassed from rust, so the delegate is created via
+// pbgui_tree_highlight_delegate_new, and owned by its parent.
+#include <QAbstractTextDocumentLayout>
+#include <QApplication>
+#include <QPainter>
+#include <QStyle>
+#include <QStyleOptionViewItem>
+#include <QStyledItemDelegate>
+#include <QTextDocument>
+
+namespace {
+
+class HighlightDelegate : public QStyledItemDelegate {
+  public:
+    HighlightDelegate(int role, QObject *parent)
+        : QStyledItemDelegate(parent), m_role(role) {}
+
+    void paint(QPainter *painter, const QStyleOptionViewItem &option,
+               const QModelIndex &index) const override {
+        const QString html = index.data(m_role).toString();
+        if (html.isEmpty()) {
+            QStyledItemDelegate::paint(painter, option, index);
+            return;
+        }
+        QStyleOptionViewItem opt = option;
+        initStyleOption(&opt, index);
+        const QWidget *widget = opt.widget;
+        QStyle *style = widget ? widget->style() : QApplication::style();
+
+        // draw the background, selection, focus and icon without the text
+        opt.text = QString();
+        style->drawControl(QStyle::CE_ItemViewItem, &opt, painter, widget);
+
+        const QRect rect =
+            style->subElementRect(QStyle::SE_ItemViewItemText, &opt, widget);
+        QTextDocument doc;
+        doc.setDefaultFont(opt.font);
+        doc.setDocumentMargin(0);
+        doc.setHtml(html);
+
+        // the unmatched text takes on the item's foreground, or that of the
+        // selection
+        QAbstractTextDocumentLayout::PaintContext context;
+        const QPalette::ColorGroup group = opt.state.testFlag(QStyle::State_Enabled)
+                                               ? QPalette::Normal
+                                               : QPalette::Disabled;
+        const QPalette::ColorRole text = opt.state.testFlag(QStyle::State_Selected)
+                                             ? QPalette::HighlightedText
+                                             : QPalette::Text;
+        context.palette.setColor(QPalette::Text, opt.palette.color(group, text));
+
+        painter->save();
+        const int margin =
+            style->pixelMetric(QStyle::PM_FocusFrameHMargin, nullptr, widget) + 1;
+        const qreal top =
+            rect.top() + (rect.height() - doc.size().height()) / 2.0;
+        painter->translate(rect.left() + margin, top);
+        painter->setClipRect(QRectF(0, 0, rect.width() - margin, doc.size().height()));
+        doc.documentLayout()->draw(painter, context);
+        painter->restore();
+    }
+
+  private:
+    int m_role;
+};
+
+} // namespace
+
+extern "C" QStyledItemDelegate *pbgui_tree_highlight_delegate_new(int role,
+                                                                 QObject *parent) {
+    return new HighlightDelegate(role, parent);
+}
@@ -0,0 +1,192 @@
+use crate::pins::PinLocation;
+
+/// A request to pin a version of a package for a platform, role and level at a site
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PinRequest {
+    pub package: String,
+    pub version: String,
+    pub platform: String,
+    pub site: String,
+    pub role: String,
+    pub level: String,
+}
+
+impl PinRequest {
+    /// New up a PinRequest
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    /// * `version` - The version to pin
+    /// * `platform` - The platform (or `any`)
+    /// * `site` - The site
+    /// * `role` - The role (or `any`)
+    /// * `level` - The level (or `facility`)
+    ///
+    /// # Returns
+    /// * PinRequest instance
+    pub fn new<S: Into<String>>(
+        package: S,
+        version: S,
+        platform: S,
+        site: S,
+        role: S,
+        level: S,
+    ) -> Self {
+        Self {
+            package: package.into(),
+            version: version.into(),
+            platform: platform.into(),
+            site: site.into(),
+            role: role.into(),
+            level: level.into(),
+        }
+    }
+
+    /// Retrieve the label of the pin's coordinates (eg `lighting @ dev01.rd`)
+    pub fn label(&self) -> String {
+        format!("{} @ {}", self.role, self.level)
+    }
+
+    /// Determine whether the supplied pin shares the request's coordinates
+    pub fn targets(&self, pin: &PinLocation) -> bool {
+        pin.site == self.site
+            && pin.platform == self.platform
+            && pin.role == self.role
+            && pin.level == self.level
+    }
+
+    /// Validate the request against the known roles, levels and platforms
+    ///
+    /// # Arguments
+    /// * `roles` - The known roles
+    /// * `levels` - The known levels
+    /// * `platforms` - The known platforms
+    ///
+    /// # Returns
+    /// * Ok(()) or a description of the problem
+    pub fn validate(
+        &self,
+        roles: &[String],
+        levels: &[String],
+        platforms: &[String],
+    ) -> Result<(), String> {
+        let fields = [
+            ("package", &self.package),
+            ("version", &self.version),
+            ("platform", &self.platform),
+            ("site", &self.site),
+            ("role", &self.role),
+            ("level", &self.level),
+        ];
+        if let Some((name, _)) = fields.iter().find(|(_, value)| value.trim().is_empty()) {
+            return Err(format!("a {} is required", name));
+        }
+        let known = |values: &[String], value: &str, wildcard: &str| {
+            value == wildcard || values.iter().any(|v| v == value)
+        };
+        if !known(roles, &self.role, "any") {
+            return Err(format!("{} is not a known role", self.role));
+        }
+        if !known(levels, &self.level, "facility") {
+            return Err(format!("{} is not a known level", self.level));
+        }
+        if !known(platforms, &self.platform, "any") {
+            return Err(format!("{} is not a known platform", self.platform));
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of a pin request, as previewed by a dry run before it is written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinPreview {
+    pub request: PinRequest,
+    /// The version currently pinned at the request's coordinates, if any
+    pub replaces: Option<String>,
+}
+
+impl PinPreview {
+    /// Determine whether the request would leave the pin unchanged
+    pub fn is_noop(&self) -> bool {
+        self.replaces.as_ref() == Some(&self.request.version)
+    }
+
+    /// Describe the outcome of the request (eg
+    /// `pin houdini-18.0.2 for cent7_64 at lighting @ dev01 (portland), replacing 18.0.1`)
+    pub fn describe(&self) -> String {
+        let request = &self.request;
+        let pin = format!(
+            "pin {}-{} for {} at {} ({})",
+            request.package,
+            request.version,
+            request.platform,
+            request.label(),
+            request.site
+        );
+        match &self.replaces {
+            Some(version) if self.is_noop() => format!("{} is already pinned to {}", pin, version),
+            Some(version) => format!("{}, replacing {}", pin, version),
+            None => pin,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(platform: &str, role: &str, level: &str) -> PinRequest {
+        PinRequest::new("maya", "2020.1", platform, "portland", role, level)
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn validate(request: &PinRequest) -> Result<(), String> {
+        request.validate(
+            &names(&["lighting"]),
+            &names(&["dev01"]),
+            &names(&["cent7_64"]),
+        )
+    }
+
+    #[test]
+    fn validate_accepts_known_values_and_wildcards() {
+        assert_eq!(validate(&request("cent7_64", "lighting", "dev01")), Ok(()));
+        assert_eq!(validate(&request("any", "any", "facility")), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_missing_and_unknown_values() {
+        let mut missing = request("any", "any", "facility");
+        missing.version = " ".to_string();
+        assert_eq!(validate(&missing), Err("a version is required".to_string()));
+        assert_eq!(
+            validate(&request("any", "fx", "facility")),
+            Err("fx is not a known role".to_string())
+        );
+        assert_eq!(
+            validate(&request("any", "any", "dev02")),
+            Err("dev02 is not a known level".to_string())
+        );
+        assert_eq!(
+            validate(&request("win10_64", "any", "facility")),
+            Err("win10_64 is not a known platform".to_string())
+        );
+    }
+
+    #[test]
+    fn preview_describes_what_is_replaced() {
+        let preview = |replaces: Option<&str>| PinPreview {
+            request: request("cent7_64", "lighting", "dev01"),
+            replaces: replaces.map(|version| version.to_string()),
+        };
+        assert!(preview(Some("2020.1")).is_noop());
+        assert!(!preview(None).is_noop());
+        assert_eq!(
+            preview(Some("2019.1")).describe(),
+            "pin maya-2020.1 for cent7_64 at lighting @ dev01 (portland), replacing 2019.1"
+        );
+    }
+}
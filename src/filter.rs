@@ -0,0 +1,187 @@
+use std::fmt;
+use std::ops::Range;
+
+/// The default background color of the matched text when highlighting matches
+pub const DEFAULT_HIGHLIGHT_COLOR: &str = "#b5762a";
+
+/// How the text typed into the package filter is matched against package names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Packages containing the filter text (the default)
+    Contains,
+    /// Packages containing the filter text, ignoring case
+    ContainsIgnoreCase,
+    /// Packages starting with the filter text
+    StartsWith,
+}
+
+impl Default for FilterMode {
+    fn default() -> Self {
+        FilterMode::Contains
+    }
+}
+
+impl FilterMode {
+    /// Find the first match of the filter text within a package name
+    ///
+    /// # Arguments
+    /// * `text` - The package name
+    /// * `filter` - The filter text
+    ///
+    /// # Returns
+    /// * The byte range of the match within text, if it matches
+    pub fn find(&self, text: &str, filter: &str) -> Option<Range<usize>> {
+        match self {
+            FilterMode::Contains => text.find(filter).map(|start| start..start + filter.len()),
+            FilterMode::ContainsIgnoreCase => {
+                // lowercasing may change the length of non ascii text, so
+                // compare character by character
+                let filter = filter.to_lowercase();
+                text.char_indices().find_map(|(start, _)| {
+                    let mut end = start;
+                    let mut rest = text[start..].chars();
+                    for expected in filter.chars() {
+                        let c = rest.next()?;
+                        if c.to_lowercase().ne(expected.to_lowercase()) {
+                            return None;
+                        }
+                        end += c.len_utf8();
+                    }
+                    Some(start..end)
+                })
+            }
+            FilterMode::StartsWith => {
+                if text.starts_with(filter) {
+                    Some(0..filter.len())
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Determine whether a package name matches the filter text. Every name
+    /// matches an empty filter.
+    pub fn matches(&self, text: &str, filter: &str) -> bool {
+        filter.is_empty() || self.find(text, filter).is_some()
+    }
+
+    /// Render a package name as rich text, highlighting the first match of
+    /// the filter text
+    ///
+    /// # Arguments
+    /// * `text` - The package name
+    /// * `filter` - The filter text
+    /// * `color` - The background color of the match
+    ///
+    /// # Returns
+    /// * The html, or None if the filter is empty or does not match
+    pub fn highlight(&self, text: &str, filter: &str, color: &str) -> Option<String> {
+        if filter.is_empty() {
+            return None;
+        }
+        let range = self.find(text, filter)?;
+        Some(format!(
+            "{}<span style=\"background-color: {}\">{}</span>{}",
+            escape(&text[..range.start]),
+            color,
+            escape(&text[range.clone()]),
+            escape(&text[range.end..])
+        ))
+    }
+}
+
+// escape the characters which qt's rich text treats as markup
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// The number of packages matching the filter, out of those considered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchCount {
+    pub matched: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for MatchCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let noun = if self.total == 1 {
+            "package"
+        } else {
+            "packages"
+        };
+        if self.matched == self.total {
+            write!(f, "{} {}", self.total, noun)
+        } else {
+            write!(f, "{} of {} {}", self.matched, self.total, noun)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_finds_the_first_match() {
+        assert_eq!(
+            FilterMode::Contains.find("maya_tools", "tools"),
+            Some(5..10)
+        );
+        assert_eq!(FilterMode::Contains.find("maya_tools", "Tools"), None);
+    }
+
+    #[test]
+    fn starts_with_only_matches_the_start() {
+        assert_eq!(FilterMode::StartsWith.find("maya", "ma"), Some(0..2));
+        assert_eq!(FilterMode::StartsWith.find("amaya", "ma"), None);
+    }
+
+    #[test]
+    fn contains_ignore_case_folds_non_ascii_text() {
+        let mode = FilterMode::ContainsIgnoreCase;
+        assert_eq!(mode.find("Maya", "mAY"), Some(0..3));
+        // É and each of the greek letters occupy two bytes
+        assert_eq!(mode.find("Éclair", "éC"), Some(0..3));
+        assert_eq!(mode.find("the ΣΟΦΙΑ tool", "σοφ"), Some(4..10));
+        assert_eq!(mode.find("Éclair", "eclair"), None);
+    }
+
+    #[test]
+    fn everything_matches_an_empty_filter() {
+        assert!(FilterMode::StartsWith.matches("maya", ""));
+        assert!(!FilterMode::StartsWith.matches("maya", "x"));
+    }
+
+    #[test]
+    fn highlight_escapes_markup() {
+        assert_eq!(
+            FilterMode::Contains.highlight("a<b>&c", "b", "#fff"),
+            Some("a&lt;<span style=\"background-color: #fff\">b</span>&gt;&amp;c".to_string())
+        );
+    }
+
+    #[test]
+    fn highlight_keeps_the_case_of_the_text() {
+        assert_eq!(
+            FilterMode::ContainsIgnoreCase.highlight("Éclair", "éc", "red"),
+            Some("<span style=\"background-color: red\">Éc</span>lair".to_string())
+        );
+    }
+
+    #[test]
+    fn highlight_requires_a_match() {
+        assert_eq!(FilterMode::Contains.highlight("maya", "", "red"), None);
+        assert_eq!(FilterMode::Contains.highlight("maya", "x", "red"), None);
+    }
+
+    #[test]
+    fn match_count_displays_the_matches_out_of_the_total() {
+        let count = |matched, total| MatchCount { matched, total }.to_string();
+        assert_eq!(count(1, 1), "1 package");
+        assert_eq!(count(12, 12), "12 packages");
+        assert_eq!(count(12, 843), "12 of 843 packages");
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+/// The group of packages which do not belong to any other group
+pub const OTHER_GROUP: &str = "Other";
+
+/// Attributes of a package, retrieved from the database, by which packages may be grouped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PackageAttribute {
+    /// Whether any version of the package is pinned at the current site
+    PinnedAtSite,
+}
+
+/// How top level packages are bucketed into collapsible groups
+#[derive(Clone)]
+pub enum Grouping {
+    /// Group packages by the supplied prefixes (eg `houdini` groups
+    /// `houdini`, `houdini_engine` and `houdini_tools`). Packages are placed
+    /// in the group of the longest matching prefix.
+    Prefixes(Vec<String>),
+    /// Group packages by the text preceding the first occurrence of the
+    /// separator (eg `_` groups `maya_tools` under `maya`)
+    Separator(char),
+    /// Group packages by the name returned from a host supplied callback
+    Callback(Rc<dyn Fn(&str) -> String>),
+    /// Group packages by an attribute retrieved from the database
+    Metadata(PackageAttribute),
+}
+
+impl fmt::Debug for Grouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Grouping::Prefixes(prefixes) => f.debug_tuple("Prefixes").field(prefixes).finish(),
+            Grouping::Separator(separator) => f.debug_tuple("Separator").field(separator).finish(),
+            Grouping::Callback(_) => f.write_str("Callback(..)"),
+            Grouping::Metadata(attribute) => f.debug_tuple("Metadata").field(attribute).finish(),
+        }
+    }
+}
+
+impl Grouping {
+    /// Group packages by a host supplied callback
+    pub fn callback<F: Fn(&str) -> String + 'static>(callback: F) -> Self {
+        Grouping::Callback(Rc::new(callback))
+    }
+
+    /// Retrieve the group of a package
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    /// * `pinned` - The packages pinned at the current site, required by
+    ///   `PackageAttribute::PinnedAtSite`
+    ///
+    /// # Returns
+    /// * The name of the group
+    pub fn group_of(&self, package: &str, pinned: &HashSet<String>) -> String {
+        match self {
+            Grouping::Prefixes(prefixes) => prefixes
+                .iter()
+                .filter(|prefix| package.starts_with(prefix.as_str()))
+                .max_by_key(|prefix| prefix.len())
+                .cloned()
+                .unwrap_or_else(|| OTHER_GROUP.to_string()),
+            Grouping::Separator(separator) => match package.find(*separator) {
+                Some(idx) if idx > 0 => package[..idx].to_string(),
+                _ => package.to_string(),
+            },
+            Grouping::Callback(callback) => callback(package),
+            Grouping::Metadata(PackageAttribute::PinnedAtSite) => {
+                if pinned.contains(package) {
+                    "Pinned".to_string()
+                } else {
+                    "Not Pinned".to_string()
+                }
+            }
+        }
+    }
+
+    /// Bucket packages into groups, preserving the order of the packages
+    /// within each group
+    ///
+    /// # Arguments
+    /// * `packages` - The names of the packages
+    /// * `pinned` - The packages pinned at the current site
+    ///
+    /// # Returns
+    /// * Map of group name to the packages within it
+    pub fn group(
+        &self,
+        packages: &[String],
+        pinned: &HashSet<String>,
+    ) -> BTreeMap<String, Vec<String>> {
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for package in packages {
+            groups
+                .entry(self.group_of(package, pinned))
+                .or_default()
+                .push(package.clone());
+        }
+        groups
+    }
+
+    /// Determine whether grouping requires the packages pinned at the current site
+    pub fn requires_pins(&self) -> bool {
+        matches!(self, Grouping::Metadata(PackageAttribute::PinnedAtSite))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn prefixes_prefer_the_longest_match() {
+        let grouping = Grouping::Prefixes(names(&["houdini", "houdini_engine"]));
+        let pinned = HashSet::new();
+        assert_eq!(grouping.group_of("houdini_tools", &pinned), "houdini");
+        assert_eq!(
+            grouping.group_of("houdini_engine_maya", &pinned),
+            "houdini_engine"
+        );
+        assert_eq!(grouping.group_of("maya", &pinned), OTHER_GROUP);
+    }
+
+    #[test]
+    fn separator_groups_by_the_leading_text() {
+        let grouping = Grouping::Separator('_');
+        let pinned = HashSet::new();
+        assert_eq!(grouping.group_of("maya_tools", &pinned), "maya");
+        assert_eq!(grouping.group_of("maya", &pinned), "maya");
+        assert_eq!(grouping.group_of("_private", &pinned), "_private");
+    }
+
+    #[test]
+    fn metadata_groups_by_whether_packages_are_pinned() {
+        let grouping = Grouping::Metadata(PackageAttribute::PinnedAtSite);
+        let pinned = names(&["maya"]).into_iter().collect::<HashSet<_>>();
+        assert!(grouping.requires_pins());
+        assert_eq!(grouping.group_of("maya", &pinned), "Pinned");
+        assert_eq!(grouping.group_of("nuke", &pinned), "Not Pinned");
+    }
+
+    #[test]
+    fn group_preserves_the_order_of_the_packages() {
+        let grouping = Grouping::callback(|package| package[..1].to_string());
+        assert!(!grouping.requires_pins());
+        let groups = grouping.group(&names(&["nuke", "maya", "mari", "nukex"]), &HashSet::new());
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["m", "n"]);
+        assert_eq!(groups["m"], names(&["maya", "mari"]));
+        assert_eq!(groups["n"], names(&["nuke", "nukex"]));
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+
+/// A level of the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Level {
+    /// Collapsible groups of packages, see `grouping::Grouping`
+    Group,
+    Site,
+    Package,
+    Version,
+    Platform,
+}
+
+impl Level {
+    /// Retrieve the human friendly name of the level
+    pub fn title(&self) -> &'static str {
+        match self {
+            Level::Group => "Group",
+            Level::Site => "Site",
+            Level::Package => "Package",
+            Level::Version => "Version",
+            Level::Platform => "Platform",
+        }
+    }
+}
+
+/// Errors raised when validating a Hierarchy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyError {
+    /// The hierarchy does not have any levels
+    Empty,
+    /// A level appears more than once
+    Duplicate(Level),
+    /// Every tree describes packages, so the Package level is required
+    MissingPackage,
+    /// Versions belong to a package, so the Package level must precede the Version level
+    VersionBeforePackage,
+    /// Groups bucket the top level packages, so the Group level must be
+    /// first, directly above the Package level
+    MisplacedGroup,
+}
+
+impl fmt::Display for HierarchyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HierarchyError::Empty => write!(f, "the hierarchy does not have any levels"),
+            HierarchyError::Duplicate(level) => {
+                write!(f, "the {} level appears more than once", level.title())
+            }
+            HierarchyError::MissingPackage => write!(f, "the hierarchy lacks a Package level"),
+            HierarchyError::VersionBeforePackage => {
+                write!(f, "the Package level must precede the Version level")
+            }
+            HierarchyError::MisplacedGroup => write!(
+                f,
+                "the Group level must be first, directly above the Package level"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HierarchyError {}
+
+/// Describes the levels of the tree, from the top level rows down, eg
+/// package → version → platform (the default), site → package → version,
+/// or package → platform → version. Children are loaded lazily, level by level.
+/// The packages and versions beneath a site or platform are those pinned there
+/// (beneath a platform alone, at the current site).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hierarchy {
+    levels: Vec<Level>,
+}
+
+impl Default for Hierarchy {
+    fn default() -> Self {
+        Self {
+            levels: vec![Level::Package, Level::Version, Level::Platform],
+        }
+    }
+}
+
+impl Hierarchy {
+    /// New up a Hierarchy from its levels, top level first
+    ///
+    /// # Arguments
+    /// * `levels` - The levels of the tree
+    ///
+    /// # Returns
+    /// * Result wrapping the Hierarchy, or a HierarchyError if the levels are invalid
+    pub fn new(levels: Vec<Level>) -> Result<Self, HierarchyError> {
+        if levels.is_empty() {
+            return Err(HierarchyError::Empty);
+        }
+        for (idx, level) in levels.iter().enumerate() {
+            if levels[..idx].contains(level) {
+                return Err(HierarchyError::Duplicate(*level));
+            }
+        }
+        let hierarchy = Self { levels };
+        if let Some(depth) = hierarchy.depth_of(Level::Group) {
+            if depth != 0 || hierarchy.level(1) != Some(Level::Package) {
+                return Err(HierarchyError::MisplacedGroup);
+            }
+        }
+        match (
+            hierarchy.depth_of(Level::Package),
+            hierarchy.depth_of(Level::Version),
+        ) {
+            (None, _) => Err(HierarchyError::MissingPackage),
+            (Some(package), Some(version)) if version < package => {
+                Err(HierarchyError::VersionBeforePackage)
+            }
+            _ => Ok(hierarchy),
+        }
+    }
+
+    /// Retrieve the levels, top level first
+    pub fn levels(&self) -> &[Level] {
+        &self.levels
+    }
+
+    /// Retrieve the level at the supplied depth, if any
+    pub fn level(&self, depth: usize) -> Option<Level> {
+        self.levels.get(depth).copied()
+    }
+
+    /// Retrieve the depth of the supplied level, if it is part of the hierarchy
+    pub fn depth_of(&self, level: Level) -> Option<usize> {
+        self.levels.iter().position(|l| *l == level)
+    }
+
+    /// Determine whether the top level rows are groups of packages
+    pub fn is_grouped(&self) -> bool {
+        self.level(0) == Some(Level::Group)
+    }
+
+    /// Retrieve a copy of the hierarchy with the Group level above the
+    /// Package level. The hierarchy must start with the Package level.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping the grouped Hierarchy, or a HierarchyError
+    pub fn grouped(&self) -> Result<Self, HierarchyError> {
+        if self.is_grouped() {
+            return Ok(self.clone());
+        }
+        let mut levels = vec![Level::Group];
+        levels.extend_from_slice(&self.levels);
+        Self::new(levels)
+    }
+
+    /// Retrieve a copy of the hierarchy without the Group level
+    pub fn ungrouped(&self) -> Self {
+        Self {
+            levels: self
+                .levels
+                .iter()
+                .copied()
+                .filter(|level| *level != Level::Group)
+                .collect(),
+        }
+    }
+
+    /// Determine whether this is the default package → version → platform hierarchy
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Determine whether this is the default hierarchy, possibly beneath
+    /// groups of packages
+    pub fn is_default_or_grouped(&self) -> bool {
+        self.ungrouped().is_default()
+    }
+
+    /// Determine whether pins may be listed beneath the rows of the last
+    /// level, which requires the last level to be the platform, beneath a
+    /// version.
+    pub fn supports_pins(&self) -> bool {
+        self.levels.last() == Some(&Level::Platform) && self.depth_of(Level::Version).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_validates_the_levels() {
+        assert_eq!(Hierarchy::new(vec![]), Err(HierarchyError::Empty));
+        assert_eq!(
+            Hierarchy::new(vec![Level::Package, Level::Package]),
+            Err(HierarchyError::Duplicate(Level::Package))
+        );
+        assert_eq!(
+            Hierarchy::new(vec![Level::Site, Level::Version]),
+            Err(HierarchyError::MissingPackage)
+        );
+        assert_eq!(
+            Hierarchy::new(vec![Level::Version, Level::Package]),
+            Err(HierarchyError::VersionBeforePackage)
+        );
+        assert_eq!(
+            Hierarchy::new(vec![Level::Package, Level::Group, Level::Version]),
+            Err(HierarchyError::MisplacedGroup)
+        );
+        assert_eq!(
+            Hierarchy::new(vec![Level::Group, Level::Site, Level::Package]),
+            Err(HierarchyError::MisplacedGroup)
+        );
+        let hierarchy = Hierarchy::new(vec![Level::Site, Level::Package, Level::Version]).unwrap();
+        assert_eq!(hierarchy.depth_of(Level::Version), Some(2));
+        assert_eq!(hierarchy.level(3), None);
+        assert!(!hierarchy.supports_pins());
+    }
+
+    #[test]
+    fn grouped_adds_the_group_level_above_the_packages() {
+        let grouped = Hierarchy::default().grouped().unwrap();
+        assert_eq!(
+            grouped.levels(),
+            &[
+                Level::Group,
+                Level::Package,
+                Level::Version,
+                Level::Platform
+            ]
+        );
+        assert!(grouped.is_grouped());
+        assert!(!grouped.is_default());
+        assert!(grouped.is_default_or_grouped());
+        assert!(grouped.supports_pins());
+        assert_eq!(grouped.grouped(), Ok(grouped.clone()));
+        assert_eq!(grouped.ungrouped(), Hierarchy::default());
+    }
+
+    #[test]
+    fn grouped_requires_the_packages_at_the_top() {
+        let hierarchy = Hierarchy::new(vec![Level::Site, Level::Package]).unwrap();
+        assert_eq!(hierarchy.grouped(), Err(HierarchyError::MisplacedGroup));
+        assert!(!hierarchy.is_default_or_grouped());
+    }
+}
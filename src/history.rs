@@ -0,0 +1,58 @@
+/// The titles of the columns of the history panel, in display order
+pub const HISTORY_COLUMNS: [&str; 8] = [
+    "Date", "Author", "Action", "Site", "Pin", "Platform", "Version", "Comment",
+];
+
+/// A single change to the pins of a package, along with the revision
+/// (changeset) in which it was made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The id of the transaction which recorded the changeset
+    pub transaction_id: i64,
+    /// When the changeset was committed
+    pub datetime: String,
+    /// Who committed the changeset
+    pub author: String,
+    /// The comment supplied with the changeset
+    pub comment: String,
+    /// The kind of change (eg `Add`, `Update`, `Delete`)
+    pub action: String,
+    pub site: String,
+    pub role: String,
+    pub level: String,
+    pub platform: String,
+    /// The version pinned by the change
+    pub version: String,
+    /// The version which was pinned before the change, if any
+    pub previous_version: Option<String>,
+}
+
+impl HistoryEntry {
+    /// Retrieve the label of the pin coordinates (eg `lighting @ dev01.rd`)
+    pub fn label(&self) -> String {
+        format!("{} @ {}", self.role, self.level)
+    }
+
+    /// Describe the change (eg `2020.1 -> 2020.2` or `2020.1`)
+    pub fn describe(&self) -> String {
+        match &self.previous_version {
+            Some(previous) => format!("{} -> {}", previous, self.version),
+            None => self.version.clone(),
+        }
+    }
+
+    /// Retrieve the text displayed in each column of the history panel,
+    /// matching HISTORY_COLUMNS
+    pub fn columns(&self) -> [String; 8] {
+        [
+            self.datetime.clone(),
+            self.author.clone(),
+            self.action.clone(),
+            self.site.clone(),
+            self.label(),
+            self.platform.clone(),
+            self.describe(),
+            self.comment.clone(),
+        ]
+    }
+}
@@ -1,47 +1,204 @@
-use qt_core::{QAbstractItemModel, QModelIndex, QSize, QString, WidgetAttribute};
+use crate::backend::{Backend, BackendError};
+use crate::cache::CacheStats;
+use crate::callback::Callbacks;
+use crate::columns::{latest_version, Column};
+use crate::compare::{SiteComparison, COMPARISON_COLUMNS};
+use crate::delegate;
+use crate::edit::{PinPreview, PinRequest};
+use crate::filter::{FilterMode, MatchCount, DEFAULT_HIGHLIGHT_COLOR};
+use crate::grouping::{Grouping, OTHER_GROUP};
+use crate::hierarchy::{Hierarchy, Level};
+use crate::history::{HistoryEntry, HISTORY_COLUMNS};
+use crate::live::{Listener, LiveUpdate};
+use crate::loading::{spinner_text, LoadingMode, SPINNER_INTERVAL};
+use crate::mime;
+use crate::overlay::{LoadFailure, LoadRequest, OverlayState, OverlayText};
+use crate::pins::PinLocation;
+use crate::platform::{PlatformDescriptor, PlatformRegistry};
+use crate::selection::{DistributionSelection, SelectionMode};
+use crate::shortcuts::ShortcutAction;
+use crate::theme::{IconSet, Theme, ThemeError};
+use crate::undo::{PinChange, UndoStack};
+use crate::widget::{Guarded, WidgetError};
+use qt_core::{
+    AlignmentFlag, ContextMenuPolicy, DropAction, ItemDataRole, MouseButton, QAbstractItemModel,
+    QFileSystemWatcher, QModelIndex, QPoint, QSize, QString, QStringList, QTimer, QVariant,
+    ShortcutContext, SlotOfQString, WidgetAttribute,
+};
 use qt_gui::q_icon::{Mode, State};
-use qt_gui::QIcon;
+use qt_gui::{QBrush, QColor, QCursor, QDrag, QGuiApplication, QIcon, QKeySequence};
 use qt_gui::{QStandardItem, QStandardItemModel};
 use qt_widgets::{
-    cpp_core::{CastInto, CppBox, DynamicCast, MutPtr, StaticUpcast},
-    q_abstract_item_view::EditTrigger,
+    cpp_core::{CastInto, CppBox, DynamicCast, MutPtr, Ref, StaticUpcast},
+    q_abstract_item_view::{self, EditTrigger, SelectionBehavior},
+    q_dialog::DialogCode,
+    q_dialog_button_box::StandardButton,
     q_header_view::ResizeMode,
-    QComboBox, QFrame, QLabel, QLayout, QLineEdit, QPushButton, QTreeView, QWidget,
+    q_message_box, QAction, QApplication, QComboBox, QDialog, QDialogButtonBox, QFormLayout,
+    QFrame, QLabel, QLayout, QLineEdit, QMenu, QMessageBox, QPushButton, QShortcut, QSplitter,
+    QStackedWidget, QTableWidget, QTableWidgetItem, QTreeView, QWidget,
 };
 use rustqt_utils::{create_hlayout, create_vlayout, qs, set_stylesheet_from_str, ToQStringOwned};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+// the default maximum number of nodes loaded by a call to expand_all
+const DEFAULT_MAX_EAGER_NODES: usize = 5000;
+// the number of model columns in the default and multi column modes. The
+// pin column is always the last column.
+const DEFAULT_COLUMN_COUNT: i32 = 2;
+const MULTI_COLUMN_COUNT: i32 = 6;
+// the interval, in milliseconds, at which live updates are applied to the tree
+const LIVE_UPDATE_INTERVAL: i32 = 250;
+// the data role (one past qt's UserRole) marking the items annotated by a
+// comparison of sites
+const COMPARISON_ROLE: i32 = 0x0100 + 1;
+// the data role holding the rich text, highlighting the matches of the filter,
+// which the highlight delegate draws in place of an item's text
+const HIGHLIGHT_ROLE: i32 = 0x0100 + 2;
+// the data role marking the placeholders which display the error of a failed
+// load of their parent's children
+const LOAD_ERROR_ROLE: i32 = 0x0100 + 3;
+// the interval, in milliseconds, at which the cursor is polled while a press
+// may turn into a drag
+const DRAG_POLL_INTERVAL: i32 = 15;
+
+// evaluates to the widget (or model) wrapped by the supplied Result, or
+// returns the default value of the enclosing function's return type once the
+// widgets have been destroyed, so that setters do nothing and getters return
+// defaults
+macro_rules! live_or_default {
+    ($widget:expr) => {
+        match $widget {
+            Ok(widget) => widget,
+            Err(_) => return Default::default(),
+        }
+    };
+}
+
+// The widgets making up the header above the tree
+struct Header {
+    busy_label: MutPtr<QLabel>,
+    site_label: MutPtr<QLabel>,
+    cbox: MutPtr<QComboBox>,
+    filter_btn: MutPtr<QPushButton>,
+    expand_all_btn: MutPtr<QPushButton>,
+    collapse_all_btn: MutPtr<QPushButton>,
+}
+
+// the widgets displayed in place of the view when it is empty, or a load
+// has failed, returned by create_overlay
+struct Overlay {
+    stack: Guarded<QStackedWidget>,
+    label: Guarded<QLabel>,
+    retry_btn: Guarded<QPushButton>,
+}
+
+// ends the load begun by InnerTreeView::begin_loading when dropped, however
+// the load returns
+pub(crate) struct LoadingGuard<'a> {
+    tree: &'a InnerTreeView,
+}
+
+impl Drop for LoadingGuard<'_> {
+    fn drop(&mut self) {
+        self.tree.end_loading();
+    }
+}
+
+// a load deferred until the loading indicators have been painted
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DeferredLoad {
+    // the children of the item with the supplied path
+    Children(Vec<String>),
+    // the update of the tree for the current site
+    Site,
+}
 
-const STYLE_STR: &'static str = include_str!("../resources/tree.qss");
+// the widgets making up the history panel, returned by create_history_panel
+struct HistoryPanel {
+    dialog: Guarded<QDialog>,
+    title: Guarded<QLabel>,
+    table: Guarded<QTableWidget>,
+}
 
 /// A struct holding the QTreeView and providing a simple Api, mirrored
 /// by the parent.
 pub(crate) struct InnerTreeView {
-    parent_frame: MutPtr<QFrame>,
-    cbox: MutPtr<QComboBox>,
-    filter_cb: MutPtr<QPushButton>,
-    filter_frame: MutPtr<QFrame>,
-    filter: MutPtr<QLineEdit>,
-    view: MutPtr<QTreeView>,
+    // the frame holding the rest of the widgets, which are destroyed along
+    // with it. Each widget is guarded, as qt may destroy it independently.
+    parent_frame: Guarded<QFrame>,
+    busy_label: Guarded<QLabel>,
+    site_label: Guarded<QLabel>,
+    cbox: Guarded<QComboBox>,
+    filter_cb: Guarded<QPushButton>,
+    expand_all_btn: Guarded<QPushButton>,
+    collapse_all_btn: Guarded<QPushButton>,
+    filter_frame: Guarded<QFrame>,
+    filter: Guarded<QLineEdit>,
+    match_count_label: Guarded<QLabel>,
+    match_count: Cell<MatchCount>,
+    highlight_matches: Cell<bool>,
+    highlight_color: RefCell<String>,
+    view: Guarded<QTreeView>,
+    selection_changed: Callbacks<Vec<DistributionSelection>>,
+    drag_enabled: Cell<bool>,
+    drag_timer: Guarded<QTimer>,
+    drag_origin: Cell<Option<(i32, i32)>>,
+    shortcuts: HashMap<ShortcutAction, Guarded<QShortcut>>,
+    backend: RefCell<Backend>,
+    max_eager_nodes: Cell<usize>,
+    listener: RefCell<Option<Listener>>,
+    live_timer: Guarded<QTimer>,
+    live_updated: Callbacks<LiveUpdate>,
+    theme: RefCell<Theme>,
+    theme_watcher: Guarded<QFileSystemWatcher>,
+    platforms: RefCell<PlatformRegistry>,
+    column_actions: HashMap<Column, Guarded<QAction>>,
+    show_pins: Cell<bool>,
+    comparison: RefCell<Option<SiteComparison>>,
+    comparison_table: Guarded<QTableWidget>,
+    only_differences: Cell<bool>,
+    history_panel: HistoryPanel,
+    history: RefCell<Vec<HistoryEntry>>,
+    history_package: RefCell<Option<String>>,
+    edit_mode: Cell<bool>,
+    author: RefCell<Option<String>>,
+    pin_created: Callbacks<PinPreview>,
+    undo_stack: RefCell<UndoStack>,
+    undo_stack_changed: Callbacks<UndoStack>,
+    filter_mode: Cell<FilterMode>,
+    hierarchy: RefCell<Hierarchy>,
+    overlay: Overlay,
+    overlay_text: RefCell<OverlayText>,
+    overlay_state: Cell<Option<OverlayState>>,
+    failure: RefCell<Option<LoadFailure>>,
+    retried: Callbacks<LoadRequest>,
+    busy: Cell<usize>,
+    spinner_timer: Guarded<QTimer>,
+    spinner_frame: Cell<usize>,
+    loading_rows: RefCell<Vec<Vec<String>>>,
+    deferred_timer: Guarded<QTimer>,
+    deferred: RefCell<VecDeque<DeferredLoad>>,
+    loading_changed: Callbacks<bool>,
+    grouping: RefCell<Option<Grouping>>,
+    groups: RefCell<BTreeMap<String, Vec<String>>>,
 }
 
 impl InnerTreeView {
     /// create an InnerTreeView instance. This inner tree allows us
-    /// to use the tree's api in Slots exposed by the parent
-    pub(crate) fn create<T>(parent_widget: MutPtr<T>) -> InnerTreeView
-    where
-        T: StaticUpcast<QWidget>,
-    {
+    /// to use the tree's api in Slots exposed by the parent. The tree's
+    /// frame is created without a parent; see `attach`.
+    pub(crate) fn create() -> InnerTreeView {
         unsafe {
-            let mut qframe = QFrame::new_0a();
-            let qframe_ptr = qframe.as_mut_ptr();
+            let mut qframe_ptr = QFrame::new_0a().into_ptr();
 
             let mut layout = create_vlayout();
             let mut layout_ptr = layout.as_mut_ptr();
-            qframe.set_layout(layout.into_ptr());
+            qframe_ptr.set_layout(layout.into_ptr());
 
-            let parent_widget = parent_widget.static_upcast_mut();
-            parent_widget.layout().add_widget(qframe.into_ptr());
-
-            let (cbox_p, filter_btn) = Self::create_cbox(layout_ptr);
+            let header = Self::create_cbox(layout_ptr);
 
             let mut treeview = QTreeView::new_0a();
             treeview.set_object_name(&qs("PackageTreeView"));
@@ -49,6 +206,7 @@ impl InnerTreeView {
             let mut filter_frame = Self::new_qframe();
             let mut filter_frame_ptr = filter_frame.as_mut_ptr();
             let filter = Self::new_filter(filter_frame_ptr);
+            let match_count = Self::new_match_count(filter_frame_ptr);
             layout_ptr.add_widget(filter_frame.into_ptr());
 
             filter_frame_ptr.set_visible(false);
@@ -58,134 +216,410 @@ impl InnerTreeView {
             treeview_ptr.set_items_expandable(true);
             treeview_ptr.set_uniform_row_heights(true);
             treeview_ptr.set_header_hidden(true);
+            treeview_ptr.set_selection_behavior(SelectionBehavior::SelectRows);
+            treeview_ptr.set_context_menu_policy(ContextMenuPolicy::CustomContextMenu);
 
-            //parent_widget.layout().add_widget(treeview.into_ptr());
-            layout_ptr.add_widget(treeview.into_ptr());
+            // the view shares a stack with the overlay displayed in its place
+            let overlay = Self::create_overlay(treeview);
+            // the side by side comparison of two sites shares a splitter with the stack
+            let (splitter, comparison_table) =
+                Self::create_comparison_table(overlay.stack.as_mut_ptr());
+            layout_ptr.add_widget(splitter);
 
-            let mut model = QStandardItemModel::new_0a();
-            model.set_column_count(2);
+            // the view owns the model, which is destroyed along with it
+            let mut model = QStandardItemModel::new_1a(treeview_ptr);
+            model.set_column_count(DEFAULT_COLUMN_COUNT);
 
             treeview_ptr.set_model(model.into_ptr());
+            // the view owns the delegate which highlights the matches of the filter
+            let delegate =
+                delegate::highlight_delegate(HIGHLIGHT_ROLE, treeview_ptr.static_upcast_mut());
+            treeview_ptr.set_item_delegate(delegate);
             treeview_ptr.header().resize_section(1, 20);
+            let column_actions = Self::create_column_actions(treeview_ptr);
             treeview_ptr.header().set_stretch_last_section(false);
             treeview_ptr
                 .header()
                 .set_section_resize_mode_2a(0, ResizeMode::Stretch);
 
+            let mut live_timer = QTimer::new_1a(qframe_ptr);
+            live_timer.set_interval(LIVE_UPDATE_INTERVAL);
+
+            let mut drag_timer = QTimer::new_1a(qframe_ptr);
+            drag_timer.set_interval(DRAG_POLL_INTERVAL);
+
+            let mut spinner_timer = QTimer::new_1a(qframe_ptr);
+            spinner_timer.set_interval(SPINNER_INTERVAL);
+
+            // fires once control returns to the event loop, after the
+            // loading indicators have been painted
+            let mut deferred_timer = QTimer::new_1a(qframe_ptr);
+            deferred_timer.set_single_shot(true);
+            deferred_timer.set_interval(0);
+
+            let theme_watcher = QFileSystemWatcher::new_1a(qframe_ptr);
+
+            let history_panel = Self::create_history_panel(qframe_ptr);
+
+            let shortcuts =
+                Self::create_shortcuts(qframe_ptr, filter_frame_ptr, filter, treeview_ptr);
+
             let itv = InnerTreeView {
-                parent_frame: qframe_ptr,
-                cbox: cbox_p,
-                filter_cb: filter_btn,
-                filter_frame: filter_frame_ptr,
-                filter,
-                view: treeview_ptr.clone(),
+                parent_frame: Guarded::new(qframe_ptr),
+                busy_label: Guarded::new(header.busy_label),
+                site_label: Guarded::new(header.site_label),
+                cbox: Guarded::new(header.cbox),
+                filter_cb: Guarded::new(header.filter_btn),
+                expand_all_btn: Guarded::new(header.expand_all_btn),
+                collapse_all_btn: Guarded::new(header.collapse_all_btn),
+                filter_frame: Guarded::new(filter_frame_ptr),
+                filter: Guarded::new(filter),
+                match_count_label: Guarded::new(match_count),
+                match_count: Cell::new(MatchCount::default()),
+                highlight_matches: Cell::new(true),
+                highlight_color: RefCell::new(DEFAULT_HIGHLIGHT_COLOR.to_string()),
+                view: Guarded::new(treeview_ptr),
+                selection_changed: Callbacks::new(),
+                drag_enabled: Cell::new(false),
+                drag_timer: Guarded::new(drag_timer.into_ptr()),
+                drag_origin: Cell::new(None),
+                shortcuts,
+                backend: RefCell::new(Backend::new()),
+                max_eager_nodes: Cell::new(DEFAULT_MAX_EAGER_NODES),
+                listener: RefCell::new(None),
+                live_timer: Guarded::new(live_timer.into_ptr()),
+                live_updated: Callbacks::new(),
+                theme: RefCell::new(Theme::default()),
+                theme_watcher: Guarded::new(theme_watcher.into_ptr()),
+                platforms: RefCell::new(PlatformRegistry::default()),
+                column_actions,
+                show_pins: Cell::new(false),
+                comparison: RefCell::new(None),
+                comparison_table: Guarded::new(comparison_table),
+                only_differences: Cell::new(false),
+                history_panel,
+                history: RefCell::new(Vec::new()),
+                history_package: RefCell::new(None),
+                edit_mode: Cell::new(false),
+                author: RefCell::new(None),
+                pin_created: Callbacks::new(),
+                undo_stack: RefCell::new(UndoStack::default()),
+                undo_stack_changed: Callbacks::new(),
+                filter_mode: Cell::new(FilterMode::default()),
+                hierarchy: RefCell::new(Hierarchy::default()),
+                overlay,
+                overlay_text: RefCell::new(OverlayText::default()),
+                overlay_state: Cell::new(None),
+                failure: RefCell::new(None),
+                retried: Callbacks::new(),
+                busy: Cell::new(0),
+                spinner_timer: Guarded::new(spinner_timer.into_ptr()),
+                spinner_frame: Cell::new(0),
+                loading_rows: RefCell::new(Vec::new()),
+                deferred_timer: Guarded::new(deferred_timer.into_ptr()),
+                deferred: RefCell::new(VecDeque::new()),
+                loading_changed: Callbacks::new(),
+                grouping: RefCell::new(None),
+                groups: RefCell::new(BTreeMap::new()),
             };
+            itv.apply_icons();
 
             itv
         }
     }
 
     /// Retreive the model from the view
-    pub(crate) fn model(&self) -> MutPtr<QStandardItemModel> {
+    ///
+    /// # Returns
+    /// * Result wrapping the model, or WidgetError::Destroyed once the view
+    ///   (and the model it owns) has been destroyed
+    pub(crate) fn model(&self) -> Result<MutPtr<QStandardItemModel>, WidgetError> {
+        let view = self.view()?;
         unsafe {
-            let model = self.view.model();
+            let model: MutPtr<QStandardItemModel> =
+                QAbstractItemModel::dynamic_cast_mut(view.model());
             if model.is_null() {
-                panic!("Unable to retrieve modelfrom model pointer obtained via view.model()");
+                return Err(WidgetError::Destroyed);
             }
-            QAbstractItemModel::dynamic_cast_mut(model)
+            Ok(model)
         }
     }
 
-    #[allow(dead_code)]
-    /// Retrieve a MutPtr to the main QFrame
-    pub(crate) fn main(&self) -> MutPtr<QFrame> {
-        self.parent_frame
+    /// Retrieve the main QFrame, so that it may be placed in any layout,
+    /// splitter or dock. A frame without a parent is deleted by
+    /// delete_unattached when the tree is dropped.
+    ///
+    /// # Returns
+    /// * Result wrapping the MutPtr, or WidgetError::Destroyed
+    pub(crate) fn widget(&self) -> Result<MutPtr<QFrame>, WidgetError> {
+        self.parent_frame.get()
+    }
+
+    /// Determine whether the tree's widgets have been destroyed
+    pub(crate) fn is_destroyed(&self) -> bool {
+        !self.parent_frame.is_alive()
+    }
+
+    /// Add the main QFrame to the layout of the supplied parent widget
+    ///
+    /// # Arguments
+    /// * `parent_widget` - The widget whose layout the tree is added to
+    ///
+    /// # Returns
+    /// * Ok(()), or a WidgetError if the parent has no layout or the tree has been destroyed
+    pub(crate) fn attach<T>(&self, parent_widget: MutPtr<T>) -> Result<(), WidgetError>
+    where
+        T: StaticUpcast<QWidget>,
+    {
+        let frame = self.widget()?;
+        unsafe {
+            let parent_widget: MutPtr<QWidget> = parent_widget.static_upcast_mut();
+            let mut layout = parent_widget.layout();
+            if layout.is_null() {
+                return Err(WidgetError::MissingLayout);
+            }
+            layout.add_widget(frame);
+        }
+        Ok(())
+    }
+
+    /// Delete the main QFrame, along with the rest of the widgets, if it has
+    /// not been given a parent. Frames which have been placed are owned by
+    /// their parent. A frame added to a layout which has yet to be installed
+    /// has no parent, and so is deleted too.
+    pub(crate) fn delete_unattached(&self) {
+        if let Ok(frame) = self.widget() {
+            unsafe {
+                if frame.parent_widget().is_null() {
+                    drop(CppBox::new(frame));
+                }
+            }
+        }
     }
 
     /// Retrieve a mutable pointer to the combobox
-    pub(crate) fn combobox(&self) -> MutPtr<QComboBox> {
-        self.cbox
+    pub(crate) fn combobox(&self) -> Result<MutPtr<QComboBox>, WidgetError> {
+        self.cbox.get()
     }
 
     #[allow(dead_code)]
     /// retieve a MutPtr to the filter pushbutton
-    pub(crate) fn filter_button(&self) -> MutPtr<QPushButton> {
-        self.filter_cb
+    pub(crate) fn filter_button(&self) -> Result<MutPtr<QPushButton>, WidgetError> {
+        self.filter_cb.get()
     }
 
-    #[allow(dead_code)]
     /// Retrieve a MutPtr to the filter's frame
-    pub(crate) fn filter_frame(&self) -> MutPtr<QFrame> {
-        self.filter_frame
+    pub(crate) fn filter_frame(&self) -> Result<MutPtr<QFrame>, WidgetError> {
+        self.filter_frame.get()
     }
 
     /// Retrieve a MutPtr to the filter QLineEdit
-    pub(crate) fn filter(&self) -> MutPtr<QLineEdit> {
-        self.filter
+    pub(crate) fn filter(&self) -> Result<MutPtr<QLineEdit>, WidgetError> {
+        self.filter.get()
     }
 
     /// Retrieve a MutPTr to the QTreeView view
-    pub(crate) fn view(&self) -> MutPtr<QTreeView> {
-        self.view
+    pub(crate) fn view(&self) -> Result<MutPtr<QTreeView>, WidgetError> {
+        self.view.get()
     }
 
     /// set the row as hidden
     pub(crate) unsafe fn set_row_hidden(&self, row: i32, hidden: bool) {
         let root = QModelIndex::new();
-        let mut view = self.view;
+        let mut view = live_or_default!(self.view());
         view.set_row_hidden(row, root.as_ref(), hidden);
     }
 
-    /// Given a type that implements ToQstringOwned, append a distribution
+    /// Given a type that implements ToQstringOwned, append a package, along
+    /// with the placeholder child which allows it to be expanded. Packages
+    /// which are already present are ignored.
     pub(crate) fn add_package<T: ToQStringOwned>(&self, input: T) {
+        let package = unsafe { input.to_qstring().to_std_string() };
+        if self.hierarchy.borrow().is_grouped() {
+            if !self.grouped_packages().contains(&package) {
+                self.add_to_groups(vec![package]);
+            }
+            return;
+        }
+        if self.find_top_level_row(&package).is_some() {
+            return;
+        }
         unsafe {
-            let mut model = self.model();
-            let icon = QIcon::from_q_string(&QString::from_std_str(":/images/package_md.png"));
-            let row_count = model.row_count_0a();
+            let model = live_or_default!(self.model());
+            let icon = self.level_icon(self.root_level());
             let mut parent = model.invisible_root_item();
-            let mut item = QStandardItem::new();
-            item.set_text(&input.to_qstring());
-            item.set_icon(&icon);
-            item.set_editable(false);
-            parent.append_row_q_standard_item(item.into_ptr());
-            model.set_row_count(row_count + 1);
+            parent.append_row_q_standard_item(self.new_item(&package, icon.as_ref(), 0).into_ptr());
+            let idx = model.index_2a(parent.row_count() - 1, 0);
+            self.package_added(idx);
         }
     }
 
-    /// Clear the package list from the model
-    pub(crate) fn clear_packages(&self) {
-        unsafe {
-            let mut model = self.model();
-            for c in (0..model.row_count_0a()).rev() {
-                model.clear_item_data(&self.model().index_2a(c, 0));
+    /// Remove a package, along with its children
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    ///
+    /// # Returns
+    /// * Whether the package was present
+    pub(crate) fn remove_package(&self, package: &str) -> bool {
+        if self.hierarchy.borrow().is_grouped() {
+            let mut packages = self.grouped_packages();
+            if !packages.iter().any(|p| p == package) {
+                return false;
             }
-            model.set_row_count(0)
+            packages.retain(|p| p != package);
+            if let Err(err) = self.set_groups(packages) {
+                log::warn!("Unable to group the packages: {}", err);
+            }
+            return true;
+        }
+        let row = match self.find_top_level_row(package) {
+            Some(row) => row,
+            None => return false,
+        };
+        unsafe {
+            // removing the row deletes its items, children included
+            live_or_default!(self.model()).remove_rows_2a(row, 1);
+        }
+        self.apply_filter();
+        true
+    }
+
+    /// Remove every package, along with its children, preserving the
+    /// columns of the model
+    pub(crate) fn clear(&self) {
+        unsafe {
+            let mut model = live_or_default!(self.model());
+            // unlike model.clear(), removing the rows leaves the columns
+            // (and the header labels of multi column mode) intact
+            model.remove_rows_2a(0, model.row_count_0a());
         }
+        self.groups.borrow_mut().clear();
+        self.apply_filter();
+    }
+
+    /// Clear the package list from the model. Equivalent to `clear`.
+    pub(crate) fn clear_packages(&self) {
+        self.clear();
     }
 
-    /// Given a vector of a type that implements ToQstringOwned, append a distribution
+    /// Given a vector of a type that implements ToQstringOwned, replace the
+    /// extant packages with the supplied packages. Rather than rebuilding the
+    /// rows, only the packages which are new are inserted, and only those
+    /// which are absent are removed, so the extant packages keep their loaded
+    /// children and expansion state. Duplicates are ignored.
     pub(crate) fn set_packages<T: ToQStringOwned>(&self, inputs: Vec<T>) {
+        let mut seen = HashSet::new();
+        let packages = inputs
+            .iter()
+            .map(|input| unsafe { input.to_qstring().to_std_string() })
+            .filter(|package| seen.insert(package.clone()))
+            .collect::<Vec<_>>();
+        if self.hierarchy.borrow().is_grouped() {
+            if let Err(err) = self.set_groups(packages) {
+                log::warn!("Unable to group the packages: {}", err);
+            }
+            return;
+        }
         unsafe {
-            let mut model = self.model();
-            let mut parent = model.invisible_root_item();
-            //model.clear(); // this removes columns as well. and segfaults
-            let row_cnt = inputs.len() as i32;
-            //
-            let icon = QIcon::from_q_string(&QString::from_std_str(":/images/package_md.png"));
-            for input in inputs {
-                let mut item = QStandardItem::new();
-                let txt = input.to_qstring();
-                item.set_text(&txt);
-                item.set_icon(&icon);
-                item.set_editable(false);
-                // add one fake item to force qt to draw a
-                let mut child = QStandardItem::new();
-                child.set_text(&qs(""));
-                child.set_editable(false);
-                item.append_row_q_standard_item(child.into_ptr());
-                parent.append_row_q_standard_item(item.into_ptr());
+            let icon = self.level_icon(self.root_level());
+            let parent = live_or_default!(self.model()).invisible_root_item();
+            self.sync_rows(parent, &packages, |package| {
+                self.new_item(package, icon.as_ref(), 0)
+            });
+        }
+        self.packages_changed();
+    }
+
+    // a new item at the supplied depth, along with the empty placeholder
+    // child which forces qt to draw the expand control, if there are levels
+    // beneath it
+    unsafe fn new_item(&self, name: &str, icon: Ref<QIcon>, depth: usize) -> CppBox<QStandardItem> {
+        let mut item = QStandardItem::new();
+        item.set_text(&qs(name));
+        item.set_icon(icon);
+        item.set_editable(false);
+        if self.needs_placeholder(depth) {
+            let mut child = QStandardItem::new();
+            child.set_text(&qs(""));
+            child.set_editable(false);
+            item.append_row_q_standard_item(child.into_ptr());
+        }
+        item
+    }
+
+    // bring the children of parent in line with the supplied names, removing
+    // the rows which are no longer named and inserting those which are new,
+    // in the order of the names. The extant rows, along with their loaded
+    // children, are left intact, even if they are out of order.
+    unsafe fn sync_rows<F>(&self, parent: MutPtr<QStandardItem>, names: &[String], new_item: F)
+    where
+        F: Fn(&str) -> CppBox<QStandardItem>,
+    {
+        let mut parent = parent;
+        let wanted = names.iter().collect::<HashSet<_>>();
+        for row in (0..parent.row_count()).rev() {
+            if !wanted.contains(&Self::item_name(parent.child_2a(row, 0))) {
+                parent.remove_row(row);
+            }
+        }
+        let positions = (0..parent.row_count())
+            .map(|row| (Self::item_name(parent.child_2a(row, 0)), row))
+            .collect::<HashMap<_, _>>();
+        // the row at which the next new item is inserted, and the number of
+        // items inserted so far, which shifts the extant rows beneath them
+        let mut row = 0;
+        let mut inserted = 0;
+        for name in names {
+            match positions.get(name) {
+                Some(position) => {
+                    if position + inserted >= row {
+                        row = position + inserted + 1;
+                    }
+                }
+                None => {
+                    parent.insert_row_int_q_standard_item(row, new_item(name).into_ptr());
+                    row += 1;
+                    inserted += 1;
+                }
             }
-            model.set_row_count(row_cnt);
+        }
+    }
+
+    // bring the optional columns, comparison and filter up to date with the
+    // top level items
+    fn packages_changed(&self) {
+        self.fill_package_columns();
+        if self.comparison.borrow().is_some() {
+            self.annotate_packages();
+        }
+        self.apply_filter();
+    }
+
+    // bring the optional columns, comparison and filter up to date with a
+    // single new package, leaving the extant rows alone
+    unsafe fn package_added(&self, idx: CppBox<QModelIndex>) {
+        let model = live_or_default!(self.model());
+        if self.comparison.borrow().is_some() {
+            self.annotate_package(model.item_from_index(&idx));
+        }
+        let filter = live_or_default!(self.filter()).text().to_std_string();
+        let mut count = self.match_count.get();
+        count.total += 1;
+        if self.filter_package(idx.as_ref(), &filter) {
+            count.matched += 1;
+        }
+        self.set_match_count(count);
+        // sorting may move the row, so it is filled in last
+        self.fill_package_rows(&[idx]);
+        self.update_overlay();
+    }
+
+    // the row of the top level item with the supplied name
+    fn find_top_level_row(&self, name: &str) -> Option<i32> {
+        unsafe {
+            let model = live_or_default!(self.model());
+            (0..model.row_count_0a()).find(|row| Self::item_name(model.item_2a(*row, 0)) == name)
         }
     }
 
@@ -219,7 +653,7 @@ impl InnerTreeView {
     /// # Returns None
     pub(crate) fn remove_sites(&self) {
         unsafe {
-            self.combobox().clear();
+            live_or_default!(self.combobox()).clear();
         }
     }
 
@@ -235,6 +669,7 @@ impl InnerTreeView {
         I: AsRef<str>,
     {
         unsafe {
+            let mut cbox = live_or_default!(self.combobox());
             self.remove_sites();
             let mut idx = 0;
             let mut cnt = 0;
@@ -242,10 +677,10 @@ impl InnerTreeView {
                 if current.as_ref() == item.as_ref() {
                     idx = cnt;
                 }
-                self.combobox().add_item_q_string(&qs(item.as_ref()));
+                cbox.add_item_q_string(&qs(item.as_ref()));
                 cnt += 1;
             }
-            self.combobox().set_current_index(idx);
+            cbox.set_current_index(idx);
         }
     }
 
@@ -270,7 +705,6 @@ impl InnerTreeView {
         I: ToQStringOwned,
     {
         unsafe {
-            let mut cnt = 0;
             let mut parent = parent;
             for child in children {
                 let mut item = QStandardItem::new();
@@ -287,114 +721,3233 @@ impl InnerTreeView {
                 let mut icon_item = QStandardItem::new();
                 icon_item.set_editable(false);
                 parent.append_row_q_standard_item(item.into_ptr());
-                parent.set_child_3a(cnt, 1, icon_item.into_ptr());
-                cnt += 1;
+                // the row just appended, which follows any the parent already had
+                parent.set_child_3a(
+                    parent.row_count() - 1,
+                    self.pin_column(),
+                    icon_item.into_ptr(),
+                );
             }
         }
     }
 
-    /// Clear the current selection
+    /// Set the selection mode of the view
     ///
     /// # Arguments
-    /// * None
+    /// * `mode` - The SelectionMode to apply
     ///
     /// # Returns
     /// * None
-    pub(crate) fn clear_selection(&self) {
+    pub(crate) fn set_selection_mode(&self, mode: SelectionMode) {
         unsafe {
-            self.view.selection_model().clear_selection();
+            let mut view = live_or_default!(self.view());
+            match mode {
+                SelectionMode::Single => {
+                    view.set_selection_mode(q_abstract_item_view::SelectionMode::SingleSelection)
+                }
+                SelectionMode::Extended => {
+                    view.set_selection_mode(q_abstract_item_view::SelectionMode::ExtendedSelection)
+                }
+            }
         }
     }
 
-    /// Retrieve the filter combobox
+    /// Retrieve the selection mode of the view
+    pub(crate) fn selection_mode(&self) -> SelectionMode {
+        let view = live_or_default!(self.view());
+        unsafe {
+            if view.selection_mode() == q_abstract_item_view::SelectionMode::ExtendedSelection {
+                SelectionMode::Extended
+            } else {
+                SelectionMode::Single
+            }
+        }
+    }
+
+    /// Retrieve the callbacks invoked when the selection changes
+    pub(crate) fn selection_changed(&self) -> &Callbacks<Vec<DistributionSelection>> {
+        &self.selection_changed
+    }
+
+    /// Retrieve the name of the site currently selected in the combobox
+    pub(crate) fn current_site(&self) -> String {
+        let cbox = live_or_default!(self.combobox());
+        unsafe { cbox.current_text().to_std_string() }
+    }
+
+    /// Retrieve whether the tree is in multi column mode
+    pub(crate) fn is_multi_column(&self) -> bool {
+        let model = live_or_default!(self.model());
+        unsafe { model.column_count_0a() == MULTI_COLUMN_COUNT }
+    }
+
+    /// Retrieve the model column which holds the pin marker
+    pub(crate) fn pin_column(&self) -> i32 {
+        let model = live_or_default!(self.model());
+        unsafe { model.column_count_0a() - 1 }
+    }
+
+    /// Retrieve the header action toggling the visibility of a column
+    pub(crate) fn column_action(&self, column: Column) -> Result<MutPtr<QAction>, WidgetError> {
+        self.column_actions[&column].get()
+    }
+
+    // whether the header action of an optional column is checked
+    unsafe fn column_checked(&self, column: Column) -> bool {
+        live_or_default!(self.column_action(column)).is_checked()
+    }
+
+    /// Switch between the default, headerless, two column mode and multi column
+    /// mode, in which a sortable header is shown along with the optional columns.
+    /// The columns are rebuilt over the extant rows, which are kept, along with
+    /// their loaded children and pins.
     ///
     /// # Arguments
-    /// * None
+    /// * `enabled` - Whether multi column mode is enabled
     ///
     /// # Returns
-    /// * MutPtr of QPushButton
-    pub(crate) fn filter_cb(&self) -> MutPtr<QPushButton> {
-        self.filter_cb
+    /// * None
+    pub(crate) fn set_multi_column(&self, enabled: bool) {
+        unsafe {
+            let mut model = live_or_default!(self.model());
+            let mut view = live_or_default!(self.view());
+            let mut header = view.header();
+            let from = self.pin_column();
+            let to = if enabled {
+                MULTI_COLUMN_COUNT - 1
+            } else {
+                DEFAULT_COLUMN_COUNT - 1
+            };
+            if from != to {
+                Self::move_pin_column(model.invisible_root_item(), from, to);
+            }
+            if enabled {
+                let mut labels = QStringList::new();
+                labels.append_q_string(&qs("Package"));
+                for column in Column::ALL.iter() {
+                    labels.append_q_string(&qs(column.title()));
+                }
+                labels.append_q_string(&qs(""));
+                model.set_horizontal_header_labels(&labels);
+                for column in Column::ALL.iter() {
+                    header.set_section_resize_mode_2a(column.index(), ResizeMode::ResizeToContents);
+                    view.set_column_hidden(column.index(), !self.column_checked(*column));
+                }
+            }
+            header.set_section_resize_mode_2a(0, ResizeMode::Stretch);
+            header.resize_section(self.pin_column(), 20);
+            view.set_header_hidden(!enabled);
+            view.set_sorting_enabled(enabled);
+            if enabled && from != to {
+                // fill in the optional columns of the rows loaded already
+                self.fill_package_columns();
+                if self.hierarchy.borrow().is_default_or_grouped() {
+                    let packages = self
+                        .package_parents()
+                        .iter()
+                        .flat_map(|parent| self.child_indexes(parent.as_ref()))
+                        .collect::<Vec<_>>();
+                    for idx in packages {
+                        self.fill_version_columns(idx.as_ref());
+                    }
+                }
+            }
+        }
     }
 
-    /// Turn visibility of frame off and on
+    // move the pins of the children of parent, and of their descendants,
+    // from one column to another, discarding the optional columns
+    unsafe fn move_pin_column(parent: MutPtr<QStandardItem>, from: i32, to: i32) {
+        let mut parent = parent;
+        for row in 0..parent.row_count() {
+            let child = parent.child_2a(row, 0);
+            if !child.is_null() && child.has_children() {
+                Self::move_pin_column(child, from, to);
+            }
+        }
+        let pins = (0..parent.row_count())
+            .map(|row| parent.take_child_2a(row, from))
+            .collect::<Vec<_>>();
+        // only the first column survives, before the columns are added back
+        parent.set_column_count(1);
+        parent.set_column_count(to + 1);
+        for (row, pin) in pins.into_iter().enumerate() {
+            if !pin.is_null() {
+                parent.set_child_3a(row as i32, to, pin);
+            }
+        }
+    }
+
+    /// Show or hide an optional column
     ///
     /// # Arguments
-    /// * `visible` - boolean indicating the visibility state
+    /// * `column` - The Column
+    /// * `visible` - Whether the column is visible
     ///
     /// # Returns
     /// * None
-    pub(crate) fn set_filter_visibility(&self, visible: bool) {
+    pub(crate) fn set_column_visible(&self, column: Column, visible: bool) {
         unsafe {
-            let mut filter_frame = self.filter_frame;
-            filter_frame.set_visible(visible);
+            let mut action = live_or_default!(self.column_action(column));
+            if action.is_checked() != visible {
+                // toggling the action calls back into set_column_visible
+                action.set_checked(visible);
+                return;
+            }
+            if self.is_multi_column() {
+                let mut view = live_or_default!(self.view());
+                view.set_column_hidden(column.index(), !visible);
+            }
         }
     }
 
-    /// Set the stylesheet to the internal stylesheet
+    /// Retrieve whether an optional column is visible
+    pub(crate) fn is_column_visible(&self, column: Column) -> bool {
+        unsafe { self.is_multi_column() && self.column_checked(column) }
+    }
+
+    // build an uneditable item displaying a number, so that it sorts numerically
+    unsafe fn number_item(value: usize) -> CppBox<QStandardItem> {
+        let mut item = QStandardItem::new();
+        item.set_data_2a(
+            &QVariant::from_int(value as i32),
+            ItemDataRole::DisplayRole.to_int(),
+        );
+        item.set_editable(false);
+        item
+    }
+
+    // build an uneditable item displaying text
+    unsafe fn text_item(text: &str) -> CppBox<QStandardItem> {
+        let mut item = QStandardItem::new();
+        item.set_text(&qs(text));
+        item.set_editable(false);
+        item
+    }
+
+    /// Fill in the latest version and version count of every loaded package,
+    /// in multi column mode with the default hierarchy. The versions are
+    /// fetched in a single query.
+    pub(crate) fn fill_package_columns(&self) {
+        unsafe {
+            let packages = self
+                .package_parents()
+                .iter()
+                .flat_map(|parent| self.child_indexes(parent.as_ref()))
+                .collect::<Vec<_>>();
+            self.fill_package_rows(&packages);
+        }
+    }
+
+    // the indexes of the first column of the children of the supplied index
+    unsafe fn child_indexes(&self, parent: Ref<QModelIndex>) -> Vec<CppBox<QModelIndex>> {
+        let model = live_or_default!(self.model());
+        (0..model.row_count_1a(parent))
+            .map(|row| model.index_3a(row, 0, parent))
+            .collect()
+    }
+
+    // fill in the latest version and version count of the packages at the
+    // supplied indexes, sorting the rows which hold them
+    unsafe fn fill_package_rows(&self, packages: &[CppBox<QModelIndex>]) {
+        if packages.is_empty()
+            || !self.is_multi_column()
+            || !self.hierarchy.borrow().is_default_or_grouped()
+        {
+            return;
+        }
+        let model = live_or_default!(self.model());
+        let names = packages
+            .iter()
+            .map(|idx| Self::item_name(model.item_from_index(idx)))
+            .collect::<Vec<_>>();
+        let versions = match self.backend.borrow_mut().versions_for(&names) {
+            Ok(versions) => versions,
+            Err(e) => {
+                log::warn!("Unable to retrieve versions for columns: {}", e);
+                return;
+            }
+        };
+        let mut parents: Vec<CppBox<QModelIndex>> = Vec::new();
+        for (idx, name) in packages.iter().zip(names.iter()) {
+            let parent = idx.parent();
+            let mut parent_item = if parent.is_valid() {
+                model.item_from_index(&parent)
+            } else {
+                model.invisible_root_item()
+            };
+            let row = idx.row();
+            let package_versions = versions.get(name).cloned().unwrap_or_default();
+            let latest = latest_version(package_versions.iter()).unwrap_or("");
+            parent_item.set_child_3a(
+                row,
+                Column::LatestVersion.index(),
+                Self::text_item(latest).into_ptr(),
+            );
+            parent_item.set_child_3a(
+                row,
+                Column::VersionCount.index(),
+                Self::number_item(package_versions.len()).into_ptr(),
+            );
+            parent_item.set_child_3a(row, self.pin_column(), Self::text_item("").into_ptr());
+            let known = parents
+                .iter()
+                .any(|p| p.row() == parent.row() && p.internal_id() == parent.internal_id());
+            if !known {
+                parents.push(parent);
+            }
+        }
+        for parent in &parents {
+            self.sort_children(parent.as_ref());
+        }
+    }
+
+    /// Fill in the pinned site count and pinned platform count of each version
+    /// of the package at the supplied index, in multi column mode.
+    pub(crate) unsafe fn fill_version_columns(&self, idx: Ref<QModelIndex>) {
+        if !self.is_multi_column() || self.needs_children(idx) {
+            return;
+        }
+        let mut package_item = live_or_default!(self.model()).item_from_index(idx);
+        let package = package_item.text().to_std_string();
+        let counts = match self.backend.borrow_mut().pin_counts(&package) {
+            Ok(counts) => counts,
+            Err(e) => {
+                log::warn!("Unable to retrieve version columns for {}: {}", package, e);
+                return;
+            }
+        };
+        for row in 0..package_item.row_count() {
+            let version = package_item.child_2a(row, 0).text().to_std_string();
+            let version_counts = counts.get(&version).cloned().unwrap_or_default();
+            package_item.set_child_3a(
+                row,
+                Column::PinnedSiteCount.index(),
+                Self::number_item(version_counts.sites).into_ptr(),
+            );
+            package_item.set_child_3a(
+                row,
+                Column::PlatformCount.index(),
+                Self::number_item(version_counts.platforms).into_ptr(),
+            );
+        }
+        self.sort_children(idx);
+    }
+
+    /// Sort the children of the supplied index, or the top level items if it
+    /// is invalid, by the column and order of the header's sort indicator.
+    /// Sorting from the header only orders the rows loaded at the time, so
+    /// rows are sorted again as they replace their placeholders.
+    pub(crate) unsafe fn sort_children(&self, idx: Ref<QModelIndex>) {
+        let view = live_or_default!(self.view());
+        let mut model = live_or_default!(self.model());
+        if !self.is_multi_column() || !view.is_sorting_enabled() {
+            return;
+        }
+        let header = view.header();
+        let column = header.sort_indicator_section();
+        let order = header.sort_indicator_order();
+        if idx.is_valid() {
+            model.item_from_index(idx).sort_children_2a(column, order);
+        } else {
+            model.sort_2a(column, order);
+        }
+    }
+
+    /// Retrieve the name of the item. This is the item's text, unless the
+    /// item displays a label in place of its name (eg platforms), in which case
+    /// the name is stored under the UserRole.
+    pub(crate) unsafe fn item_name(item: MutPtr<QStandardItem>) -> String {
+        let name = item.data_1a(ItemDataRole::UserRole.to_int());
+        if name.is_valid() {
+            name.to_string().to_std_string()
+        } else {
+            item.text().to_std_string()
+        }
+    }
+
+    /// Replace the PlatformRegistry used to describe platforms, updating the
+    /// platforms which have already been loaded
+    pub(crate) fn set_platform_registry(&self, registry: PlatformRegistry) {
+        *self.platforms.borrow_mut() = registry;
+        self.redecorate_platforms();
+    }
+
+    /// Retrieve a copy of the PlatformRegistry used to describe platforms
+    pub(crate) fn platform_registry(&self) -> PlatformRegistry {
+        self.platforms.borrow().clone()
+    }
+
+    /// Register a descriptor for a platform, updating the platforms which have
+    /// already been loaded
+    pub(crate) fn register_platform(&self, name: &str, descriptor: PlatformDescriptor) {
+        self.platforms.borrow_mut().register(name, descriptor);
+        self.redecorate_platforms();
+    }
+
+    /// Set the label, icon and tooltip of each platform under the supplied
+    /// version index, from the PlatformRegistry. The platform name is
+    /// preserved under the UserRole.
+    pub(crate) unsafe fn decorate_platforms(&self, idx: Ref<QModelIndex>) {
+        if self.needs_children(idx) {
+            return;
+        }
+        let model = live_or_default!(self.model());
+        let registry = self.platforms.borrow();
+        for row in 0..model.row_count_1a(idx) {
+            let mut item = model.item_from_index(&model.index_3a(row, 0, idx));
+            let name = Self::item_name(item);
+            let descriptor = registry.describe(&name);
+            item.set_data_2a(
+                &QVariant::from_q_string(&qs(&name)),
+                ItemDataRole::UserRole.to_int(),
+            );
+            item.set_text(&qs(&descriptor.label));
+            item.set_tool_tip(&qs(&name));
+            match descriptor.icon_path() {
+                Some(path) => item.set_icon(&QIcon::from_q_string(&qs(path))),
+                None => item.set_icon(&QIcon::new()),
+            }
+        }
+    }
+
+    // decorate the platforms of every loaded version
+    fn redecorate_platforms(&self) {
+        let depth = match self.hierarchy().depth_of(Level::Platform) {
+            Some(depth) => depth,
+            None => return,
+        };
+        unsafe {
+            if depth == 0 {
+                self.decorate_platforms(QModelIndex::new().as_ref());
+                return;
+            }
+            for parent in self.loaded_indexes(depth - 1) {
+                self.decorate_platforms(parent.as_ref());
+            }
+        }
+    }
+
+    /// Build a DistributionSelection from the supplied index, by walking
+    /// up the tree and collecting the name of each ancestor, which are
+    /// assigned to the selection according to the hierarchy. The site
+    /// defaults to the current site if it is not a level of the hierarchy.
     ///
     /// # Arguments
-    /// * None
+    /// * `idx` - The index of the row of interest
     ///
     /// # Returns
-    /// *None
-    pub(crate) fn set_default_stylesheet(&self) {
-        set_stylesheet_from_str(STYLE_STR, self.parent_frame);
+    /// * DistributionSelection instance
+    pub(crate) unsafe fn selection_from_index(
+        &self,
+        idx: Ref<QModelIndex>,
+    ) -> DistributionSelection {
+        let names = self.path(idx);
+        let hierarchy = self.hierarchy.borrow();
+        let named = |level: Level| {
+            hierarchy
+                .depth_of(level)
+                .and_then(|depth| names.get(depth).cloned())
+        };
+        DistributionSelection::new(
+            named(Level::Package).unwrap_or_default(),
+            named(Level::Version),
+            named(Level::Platform),
+            named(Level::Site).unwrap_or_else(|| self.current_site()),
+        )
     }
 
-    /// Change the max number of items displayed in the combobox's dropdown
-    /// list
+    /// Retrieve the currently selected rows as DistributionSelections,
+    /// skipping the placeholder rows used to draw the expand control.
     ///
     /// # Arguments
-    /// * `max` - Maximum number of visible items in the comobobox's dropdown
+    /// * None
     ///
     /// # Returns
-    /// * None
-    pub(crate) fn set_cb_max_visible_items(&self, max: i32) {
+    /// * Vector of DistributionSelection
+    pub(crate) fn selected_distributions(&self) -> Vec<DistributionSelection> {
+        let view = live_or_default!(self.view());
+        let model = live_or_default!(self.model());
         unsafe {
-            self.combobox().set_max_visible_items(max);
+            let rows = view.selection_model().selected_rows_1a(0);
+            let mut selections = Vec::with_capacity(rows.size() as usize);
+            for cnt in 0..rows.size() {
+                let idx = rows.at(cnt);
+                if Self::is_placeholder(model.item_from_index(idx)) {
+                    continue;
+                }
+                selections.push(self.selection_from_index(idx));
+            }
+            selections
         }
     }
 
-    // Create a new `CppBox`'ed QFrame instance
-    unsafe fn new_qframe() -> CppBox<QFrame> {
-        let mut qf = QFrame::new_0a();
-        qf.set_object_name(&qs("PackageFilterFrame"));
-        let layout = create_hlayout();
-        qf.set_layout(layout.into_ptr());
-        qf
+    /// Enable or disable dragging versions and platforms out of the tree
+    pub(crate) fn set_drag_enabled(&self, enabled: bool) {
+        self.drag_enabled.set(enabled);
     }
 
-    unsafe fn new_filter(parent: MutPtr<QFrame>) -> MutPtr<QLineEdit> {
-        let label = QLabel::from_q_string(&qs("Package Filter"));
-        parent.layout().add_widget(label.into_ptr());
-        let mut qle = QLineEdit::new();
-        qle.set_attribute_2a(WidgetAttribute::WAMacShowFocusRect, false);
-        qle.set_object_name(&qs("PackageFilter"));
-        let qle_ptr = qle.as_mut_ptr();
-        parent.layout().add_widget(qle.into_ptr());
-        qle_ptr
+    /// Retrieve whether dragging is enabled
+    pub(crate) fn drag_enabled(&self) -> bool {
+        self.drag_enabled.get()
     }
 
-    fn create_cbox<I>(layout: I) -> (MutPtr<QComboBox>, MutPtr<QPushButton>)
-    where
-        I: CastInto<MutPtr<QLayout>>,
-    {
-        unsafe {
-            // combo_box
-            let mut horiz_frame = QFrame::new_0a();
-            horiz_frame.set_object_name(&qs("SitesCBFrame"));
-            let mut h_layout = create_hlayout();
-            let mut h_layout_p = h_layout.as_mut_ptr();
-            horiz_frame.set_layout(h_layout.into_ptr());
+    /// Retrieve the timer which polls the cursor while a press may turn into a drag
+    pub(crate) fn drag_timer(&self) -> Result<MutPtr<QTimer>, WidgetError> {
+        self.drag_timer.get()
+    }
 
-            let mut site_l = QLabel::from_q_string(&qs("Site"));
-            site_l.set_object_name(&qs("SiteLabel"));
-            let mut icon = QIcon::new();
-            icon.add_file_2a(&qs(":/images/world.svg"), QSize::new_2a(12, 12).as_ref());
-            let pixmap = icon.pixmap_int(12);
-            site_l.set_pixmap(&pixmap);
-            h_layout_p.add_stretch_1a(1);
+    // determine whether the left mouse button is down
+    unsafe fn left_button_down() -> bool {
+        QGuiApplication::mouse_buttons().to_int() & MouseButton::LeftButton.to_int() != 0
+    }
+
+    /// Record the position of a press which may turn into a drag. The drag
+    /// starts once the cursor moves further than the application's start
+    /// drag distance with the left button held; see `track_drag`. This is a
+    /// noop if dragging is disabled, or if the left mouse button is not down.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn press(&self) {
+        if !self.drag_enabled() {
+            return;
+        }
+        unsafe {
+            if !Self::left_button_down() {
+                return;
+            }
+            let pos = QCursor::pos_0a();
+            let mut timer = live_or_default!(self.drag_timer());
+            self.drag_origin.set(Some((pos.x(), pos.y())));
+            timer.start_0a();
+        }
+    }
+
+    /// Poll the cursor after a press, starting a drag once it has moved
+    /// further than the start drag distance. Releasing the button first
+    /// leaves the press to be handled as a click.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn track_drag(&self) {
+        unsafe {
+            let origin = match self.drag_origin.get() {
+                Some(origin) if Self::left_button_down() => origin,
+                _ => {
+                    self.stop_tracking_drag();
+                    return;
+                }
+            };
+            let pos = QCursor::pos_0a();
+            let distance = (pos.x() - origin.0).abs() + (pos.y() - origin.1).abs();
+            if distance < QApplication::start_drag_distance() {
+                return;
+            }
+            self.stop_tracking_drag();
+            self.start_drag();
+        }
+    }
+
+    // stop polling the cursor for a drag
+    unsafe fn stop_tracking_drag(&self) {
+        self.drag_origin.set(None);
+        let mut timer = live_or_default!(self.drag_timer());
+        timer.stop();
+    }
+
+    /// Start a drag of the currently selected versions and platforms, carrying
+    /// them as mime data. Package rows are not draggable. This is a noop
+    /// if dragging is disabled, or if the left mouse button is not down.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn start_drag(&self) {
+        if !self.drag_enabled() {
+            return;
+        }
+        unsafe {
+            if !Self::left_button_down() {
+                return;
+            }
+            let selections = self
+                .selected_distributions()
+                .into_iter()
+                .filter(|selection| selection.version.is_some())
+                .collect::<Vec<_>>();
+            if selections.is_empty() {
+                return;
+            }
+            // the drag is parented to the view. Qt deletes it once exec returns.
+            let view = live_or_default!(self.view());
+            let mut drag = QDrag::new(view).into_ptr();
+            drag.set_mime_data(mime::to_mime_data(&selections).into_ptr());
+            drag.exec_1a(DropAction::CopyAction.into());
+        }
+    }
+
+    /// Retrieve the QShortcut associated with an action
+    pub(crate) fn shortcut(
+        &self,
+        action: ShortcutAction,
+    ) -> Result<MutPtr<QShortcut>, WidgetError> {
+        self.shortcuts[&action].get()
+    }
+
+    /// Remap the key sequence which triggers an action
+    ///
+    /// # Arguments
+    /// * `action` - The action to remap
+    /// * `key_sequence` - The new key sequence, in QKeySequence's portable text format (eg "Ctrl+F")
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_shortcut(&self, action: ShortcutAction, key_sequence: &str) {
+        unsafe {
+            let mut shortcut = live_or_default!(self.shortcut(action));
+            shortcut.set_key(&QKeySequence::from_q_string(&qs(key_sequence)));
+        }
+    }
+
+    /// Retrieve the key sequence which triggers an action
+    pub(crate) fn shortcut_key_sequence(&self, action: ShortcutAction) -> String {
+        let shortcut = live_or_default!(self.shortcut(action));
+        unsafe { shortcut.key().to_string_0a().to_std_string() }
+    }
+
+    /// Open the filter frame and give the filter focus
+    pub(crate) fn open_filter(&self) {
+        unsafe {
+            let mut filter_cb = live_or_default!(self.filter_cb());
+            filter_cb.set_checked(true);
+            let mut filter = live_or_default!(self.filter());
+            filter.set_focus_0a();
+            filter.select_all();
+        }
+    }
+
+    /// Clear the filter text, close the filter frame, and hand focus back to the view
+    pub(crate) fn close_filter(&self) {
+        unsafe {
+            let mut filter = live_or_default!(self.filter());
+            filter.clear();
+            let mut filter_cb = live_or_default!(self.filter_cb());
+            filter_cb.set_checked(false);
+            let mut view = live_or_default!(self.view());
+            view.set_focus_0a();
+        }
+    }
+
+    /// Make the first visible package current, and give the view focus. When
+    /// the packages sit beneath groups, sites or platforms, the rows above
+    /// them are loaded as needed, and expanded, on the way down to the first
+    /// visible package.
+    pub(crate) fn jump_to_first_match(&self) {
+        let depth = match self.hierarchy.borrow().depth_of(Level::Package) {
+            Some(depth) => depth,
+            None => return,
+        };
+        unsafe {
+            let root = QModelIndex::new();
+            let idx = match self.first_visible_package(root.as_ref(), 0, depth) {
+                Some(idx) => idx,
+                None => return,
+            };
+            let mut view = live_or_default!(self.view());
+            let mut parent = idx.parent();
+            while parent.is_valid() {
+                view.expand(&parent);
+                parent = parent.parent();
+            }
+            view.set_current_index(&idx);
+            view.scroll_to_1a(&idx);
+            view.set_focus_0a();
+        }
+    }
+
+    // the first visible package at or beneath the children of parent, which
+    // are at the supplied depth, loading the children of the rows above the
+    // packages as it walks down to them
+    unsafe fn first_visible_package(
+        &self,
+        parent: Ref<QModelIndex>,
+        depth: usize,
+        package_depth: usize,
+    ) -> Option<CppBox<QModelIndex>> {
+        let model = live_or_default!(self.model());
+        let view = live_or_default!(self.view());
+        for row in 0..model.row_count_1a(parent) {
+            let idx = model.index_3a(row, 0, parent);
+            if view.is_row_hidden(row, parent) || Self::is_placeholder(model.item_from_index(&idx))
+            {
+                continue;
+            }
+            if depth == package_depth {
+                return Some(idx);
+            }
+            self.load_children(idx.as_ref());
+            if let Some(package) =
+                self.first_visible_package(idx.as_ref(), depth + 1, package_depth)
+            {
+                return Some(package);
+            }
+        }
+        None
+    }
+
+    /// Expand the supplied index and all of its descendants. As children are
+    /// loaded when their parent is expanded, this loads the full subtree.
+    pub(crate) unsafe fn expand_recursively(&self, idx: Ref<QModelIndex>) {
+        let mut view = live_or_default!(self.view());
+        view.expand(idx);
+        let model = live_or_default!(self.model());
+        for row in 0..model.row_count_1a(idx) {
+            let child = model.index_3a(row, 0, idx);
+            if model.has_children_1a(&child) {
+                self.expand_recursively(child.as_ref());
+            }
+        }
+    }
+
+    /// Fully expand the package owning the current index
+    pub(crate) fn expand_current_package(&self) {
+        unsafe {
+            let mut idx = live_or_default!(self.view()).current_index();
+            if !idx.is_valid() {
+                return;
+            }
+            while idx.parent().is_valid() {
+                idx = idx.parent();
+            }
+            let idx = live_or_default!(self.model()).index_2a(idx.row(), 0);
+            self.expand_recursively(idx.as_ref());
+        }
+    }
+
+    /// Retrieve the expand all pushbutton
+    pub(crate) fn expand_all_button(&self) -> Result<MutPtr<QPushButton>, WidgetError> {
+        self.expand_all_btn.get()
+    }
+
+    /// Retrieve the collapse all pushbutton
+    pub(crate) fn collapse_all_button(&self) -> Result<MutPtr<QPushButton>, WidgetError> {
+        self.collapse_all_btn.get()
+    }
+
+    /// Set the maximum number of nodes which a call to expand_all may load
+    pub(crate) fn set_max_eager_nodes(&self, max: usize) {
+        self.max_eager_nodes.set(max);
+    }
+
+    /// Retrieve the maximum number of nodes which a call to expand_all may load
+    pub(crate) fn max_eager_nodes(&self) -> usize {
+        self.max_eager_nodes.get()
+    }
+
+    /// Set the LoadingMode used to fetch versions
+    pub(crate) fn set_loading_mode(&self, mode: LoadingMode) {
+        self.backend.borrow_mut().set_mode(mode);
+    }
+
+    /// Retrieve the LoadingMode used to fetch versions
+    pub(crate) fn loading_mode(&self) -> LoadingMode {
+        self.backend.borrow().mode()
+    }
+
+    /// Determine whether expansions are currently being served from the
+    /// eagerly loaded index
+    pub(crate) fn is_indexed(&self) -> bool {
+        self.backend.borrow().is_indexed()
+    }
+
+    /// Set the time to live of cached query results
+    pub(crate) fn set_cache_ttl(&self, ttl: Duration) {
+        self.backend.borrow_mut().set_cache_ttl(ttl);
+    }
+
+    /// Retrieve the time to live of cached query results
+    pub(crate) fn cache_ttl(&self) -> Duration {
+        self.backend.borrow().cache_ttl()
+    }
+
+    /// Retrieve the cache hit and miss statistics
+    pub(crate) fn cache_stats(&self) -> CacheStats {
+        self.backend.borrow().cache_stats()
+    }
+
+    /// Reset the cache hit and miss statistics
+    pub(crate) fn reset_cache_stats(&self) {
+        self.backend.borrow_mut().reset_cache_stats();
+    }
+
+    /// Invalidate the cached versions of a package
+    pub(crate) fn invalidate(&self, package: &str) {
+        self.backend.borrow_mut().invalidate(package);
+    }
+
+    /// Invalidate all cached query results
+    pub(crate) fn invalidate_all(&self) {
+        self.backend.borrow_mut().invalidate_all();
+    }
+
+    /// Query the top level items from the database (the packages, unless the
+    /// hierarchy starts elsewhere) and replace the extant items with them
+    pub(crate) fn load_packages(&self) -> Result<(), BackendError> {
+        let _loading = self.begin_loading();
+        // the groups are derived from the packages
+        let level = match self.root_level() {
+            Level::Group => Level::Package,
+            level => level,
+        };
+        let items = match self.level_items(level, &[]) {
+            Ok(items) => items,
+            Err(err) => {
+                self.load_failed(LoadRequest::Packages, &err);
+                return Err(err);
+            }
+        };
+        self.load_succeeded(&LoadRequest::Packages);
+        self.set_packages(items);
+        if self.root_level() == Level::Platform {
+            unsafe { self.decorate_platforms(QModelIndex::new().as_ref()) };
+        }
+        Ok(())
+    }
+
+    /// Query the sites from the database and replace the extant sites with them
+    pub(crate) fn load_sites(&self, current: Option<&str>) -> Result<(), BackendError> {
+        let _loading = self.begin_loading();
+        let request = LoadRequest::Sites {
+            current: current.map(|site| site.to_string()),
+        };
+        let result = self.backend.borrow_mut().sites();
+        let sites = match result {
+            Ok(sites) => sites,
+            Err(err) => {
+                self.load_failed(request, &err);
+                return Err(err);
+            }
+        };
+        self.load_succeeded(&request);
+        let current = match current {
+            Some(site) => site,
+            None => sites.first().map(|site| site.as_str()).unwrap_or_default(),
+        };
+        self.set_sites(sites.iter().map(|s| s.as_str()).collect(), current);
+        Ok(())
+    }
+
+    // record a failed load, displaying the error overlay
+    fn load_failed(&self, request: LoadRequest, err: &BackendError) {
+        log::warn!("Unable to load {:?}: {}", request, err);
+        *self.failure.borrow_mut() = Some(LoadFailure {
+            request,
+            error: err.to_string(),
+        });
+        self.update_overlay();
+    }
+
+    // clear the failure of the supplied load, if it had failed
+    fn load_succeeded(&self, request: &LoadRequest) {
+        let failed = match self.failure.borrow().as_ref() {
+            Some(failure) => failure.request == *request,
+            None => false,
+        };
+        if failed {
+            *self.failure.borrow_mut() = None;
+            self.update_overlay();
+        }
+    }
+
+    /// Retrieve the last failed load, if it has yet to succeed
+    pub(crate) fn load_failure(&self) -> Option<LoadFailure> {
+        self.failure.borrow().clone()
+    }
+
+    /// Retrieve the callbacks invoked with the failed load, as it is retried
+    pub(crate) fn retried(&self) -> &Callbacks<LoadRequest> {
+        &self.retried
+    }
+
+    /// Retrieve the retry button of the error overlay
+    pub(crate) fn retry_button(&self) -> Result<MutPtr<QPushButton>, WidgetError> {
+        self.overlay.retry_btn.get()
+    }
+
+    /// Re-run the last failed load, if any
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Ok(()), or the error of the load, if it failed again
+    pub(crate) fn retry(&self) -> Result<(), BackendError> {
+        let failure = match self.failure.borrow_mut().take() {
+            Some(failure) => failure,
+            None => return Ok(()),
+        };
+        self.retried.emit(&failure.request);
+        // each load records its own failure, so the results are checked below
+        match &failure.request {
+            LoadRequest::Packages => {
+                let _ = self.load_packages();
+            }
+            LoadRequest::Sites { current } => {
+                let _ = self.load_sites(current.as_deref());
+            }
+            LoadRequest::ExpandAll { depth } => {
+                self.expand_all(*depth);
+            }
+        }
+        self.update_overlay();
+        match self.failure.borrow().as_ref() {
+            Some(failure) => Err(failure.error.clone().into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Replace the text of the overlays
+    pub(crate) fn set_overlay_text(&self, text: OverlayText) {
+        *self.overlay_text.borrow_mut() = text;
+        self.update_overlay();
+    }
+
+    /// Retrieve a copy of the text of the overlays
+    pub(crate) fn overlay_text(&self) -> OverlayText {
+        self.overlay_text.borrow().clone()
+    }
+
+    /// Retrieve the state of the overlay displayed in place of the view, if any
+    pub(crate) fn overlay_state(&self) -> Option<OverlayState> {
+        self.overlay_state.get()
+    }
+
+    // display the overlay appropriate to the current state in place of the
+    // view: the error of a failed load, the lack of packages, or the lack of
+    // packages matching the filter
+    fn update_overlay(&self) {
+        unsafe {
+            let filter = live_or_default!(self.filter()).text().to_std_string();
+            let model = live_or_default!(self.model());
+            let mut stack = live_or_default!(self.overlay.stack.get());
+            let mut label = live_or_default!(self.overlay.label.get());
+            let mut retry_btn = live_or_default!(self.overlay.retry_btn.get());
+            let state = if self.failure.borrow().is_some() {
+                Some(OverlayState::Error)
+            } else if model.row_count_0a() == 0 {
+                Some(OverlayState::Empty)
+            } else if !filter.is_empty()
+                && self.match_count.get().total > 0
+                && self.match_count.get().matched == 0
+            {
+                Some(OverlayState::NoMatches)
+            } else {
+                None
+            };
+            self.overlay_state.set(state);
+            let state = match state {
+                Some(state) => state,
+                None => {
+                    stack.set_current_index(0);
+                    return;
+                }
+            };
+            let error = self
+                .failure
+                .borrow()
+                .as_ref()
+                .map(|failure| failure.error.clone())
+                .unwrap_or_default();
+            let text = self.overlay_text.borrow();
+            label.set_text(&qs(text.render(
+                state,
+                &self.current_site(),
+                &filter,
+                &error,
+            )));
+            label.set_tool_tip(&qs(&error));
+            retry_btn.set_text(&qs(&text.retry));
+            retry_btn.set_visible(state == OverlayState::Error);
+            stack.set_current_index(1);
+        }
+    }
+
+    /// Retrieve the timer which drives the application of live updates
+    pub(crate) fn live_timer(&self) -> Result<MutPtr<QTimer>, WidgetError> {
+        self.live_timer.get()
+    }
+
+    /// Retrieve the callbacks invoked after a live update has been applied
+    pub(crate) fn live_updated(&self) -> &Callbacks<LiveUpdate> {
+        &self.live_updated
+    }
+
+    /// Start LISTENing for notifications on the supplied channel, replacing
+    /// any extant listener.
+    pub(crate) fn start_live_updates(&self, channel_name: &str) -> Result<(), BackendError> {
+        let mut timer = self.live_timer()?;
+        self.stop_live_updates();
+        let listener = Listener::spawn(channel_name)?;
+        *self.listener.borrow_mut() = Some(listener);
+        unsafe {
+            timer.start_0a();
+        }
+        Ok(())
+    }
+
+    /// Stop LISTENing for notifications
+    pub(crate) fn stop_live_updates(&self) {
+        if let Ok(mut timer) = self.live_timer() {
+            unsafe {
+                timer.stop();
+            }
+        }
+        self.listener.borrow_mut().take();
+    }
+
+    /// Determine whether we are LISTENing for notifications
+    pub(crate) fn is_live(&self) -> bool {
+        self.listener.borrow().is_some()
+    }
+
+    /// Apply any live updates which have been received since the last call
+    pub(crate) fn apply_pending_live_updates(&self) {
+        let updates = match self.listener.borrow().as_ref() {
+            Some(listener) => listener.pending(),
+            None => return,
+        };
+        for update in updates {
+            self.apply_live_update(&update);
+            self.live_updated.emit(&update);
+        }
+    }
+
+    /// Incrementally update the tree to reflect a change in the database
+    pub(crate) fn apply_live_update(&self, update: &LiveUpdate) {
+        unsafe {
+            match update {
+                LiveUpdate::Package(package) => {
+                    self.backend.borrow_mut().invalidate_packages();
+                    // packages beneath other levels are picked up when their
+                    // parents are next loaded
+                    if self.packages_at_top() && self.find_package(package).is_none() {
+                        self.add_package(package.as_str());
+                    }
+                }
+                LiveUpdate::Distribution { package, version } => {
+                    self.invalidate(package);
+                    let idx = match self.find_package(package) {
+                        Some(idx) => idx,
+                        None => {
+                            if self.packages_at_top() {
+                                self.backend.borrow_mut().invalidate_packages();
+                                self.add_package(package.as_str());
+                            }
+                            return;
+                        }
+                    };
+                    // if the versions have yet to be loaded, they will pick up
+                    // the new version when they are
+                    if self.needs_children(idx.as_ref()) {
+                        return;
+                    }
+                    let item = live_or_default!(self.model()).item_from_index(&idx);
+                    if Self::find_child_row(item, version).is_none() {
+                        self.set_children(item, vec![version.as_str()], true);
+                    }
+                }
+                LiveUpdate::VersionPin {
+                    site,
+                    package,
+                    version,
+                } => {
+                    if *site == self.current_site() {
+                        // the pin may replace that of another version, so the
+                        // markers of every loaded version are refreshed
+                        self.invalidate(package);
+                        self.set_pinned(package, version, true);
+                        self.refresh_pins(package);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find the index of the named package. Packages are only found in the
+    /// default hierarchy, where they are the top level (or within the loaded
+    /// groups), above their versions.
+    pub(crate) unsafe fn find_package(&self, package: &str) -> Option<CppBox<QModelIndex>> {
+        if !self.hierarchy.borrow().is_default_or_grouped() {
+            return None;
+        }
+        let model = live_or_default!(self.model());
+        self.package_parents().iter().find_map(|parent| {
+            (0..model.row_count_1a(parent))
+                .map(|row| model.index_3a(row, 0, parent))
+                .find(|idx| Self::item_name(model.item_from_index(idx)) == package)
+        })
+    }
+
+    // the indexes of the items whose children are packages: the root when
+    // packages are the top level, or the loaded groups
+    unsafe fn package_parents(&self) -> Vec<CppBox<QModelIndex>> {
+        let model = live_or_default!(self.model());
+        match self.root_level() {
+            Level::Package => vec![QModelIndex::new()],
+            Level::Group => (0..model.row_count_0a())
+                .map(|row| model.index_2a(row, 0))
+                .filter(|idx| !self.needs_children(idx.as_ref()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Find the row of the child of parent whose text matches
+    pub(crate) unsafe fn find_child_row(parent: MutPtr<QStandardItem>, text: &str) -> Option<i32> {
+        (0..parent.row_count()).find(|row| {
+            let child = parent.child_2a(*row, 0);
+            !child.is_null() && child.text().to_std_string() == text
+        })
+    }
+
+    /// Mark a loaded version as pinned (or not) by setting the icon in its
+    /// second column. Versions which have yet to be loaded are ignored.
+    pub(crate) fn set_pinned(&self, package: &str, version: &str, pinned: bool) {
+        unsafe {
+            let model = live_or_default!(self.model());
+            let package_item = match self.find_package(package) {
+                Some(idx) => model.item_from_index(&idx),
+                None => return,
+            };
+            let version_row = match Self::find_child_row(package_item, version) {
+                Some(version_row) => version_row,
+                None => return,
+            };
+            let mut icon_item = package_item.child_2a(version_row, self.pin_column());
+            if icon_item.is_null() {
+                return;
+            }
+            if pinned {
+                icon_item.set_icon(&QIcon::from_q_string(&qs(self.icons().pin())));
+            } else {
+                icon_item.set_icon(&QIcon::new());
+            }
+        }
+    }
+
+    /// Update the tree for the current site. Distributions are shared by
+    /// every site, so the versions loaded, and any eager index, are retained.
+    pub(crate) fn update_site(&self) {
+        let _loading = self.begin_loading();
+        let site = self.current_site();
+        // pins are per site, so discard those which have been loaded
+        if self.show_pins() {
+            self.set_show_pins(false);
+            self.set_show_pins(true);
+        }
+        let regroup = match self.grouping.borrow().as_ref() {
+            Some(grouping) => grouping.requires_pins(),
+            None => false,
+        };
+        if regroup && self.hierarchy.borrow().is_grouped() {
+            if let Err(err) = self.set_groups(self.grouped_packages()) {
+                log::warn!("Unable to regroup the packages for {}: {}", site, err);
+            }
+        }
+        // the empty overlay names the site
+        self.update_overlay();
+    }
+
+    /// Determine whether the item at the supplied index has yet to have its
+    /// children loaded. This is the case when its only child is the
+    /// placeholder, as opposed to an intended child (eg a single version or
+    /// platform). The placeholder of a failed load counts, so that expanding
+    /// the item again retries the load.
+    pub(crate) unsafe fn needs_children(&self, idx: Ref<QModelIndex>) -> bool {
+        let model = live_or_default!(self.model());
+        if model.row_count_1a(idx) != 1 {
+            return false;
+        }
+        let child = model.index_3a(0, 0, idx);
+        child.is_valid() && Self::is_placeholder(model.item_from_index(&child))
+    }
+
+    // whether the item is a placeholder: either empty, or displaying the
+    // error of a failed load
+    unsafe fn is_placeholder(item: MutPtr<QStandardItem>) -> bool {
+        item.text().to_std_string() == "" || item.data_1a(LOAD_ERROR_ROLE).to_bool()
+    }
+
+    /// Replace the empty placeholder child of the item at the supplied index
+    /// with the supplied children. If there are no children, the placeholder
+    /// is left alone.
+    ///
+    /// # Arguments
+    /// * `idx` - The index of the parent
+    /// * `children` - A Vec of type implementing ToQStringOwned
+    /// * `add_empty_gchild` - whether to add an empty grandchild to each child
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) unsafe fn replace_placeholder<I>(
+        &self,
+        idx: Ref<QModelIndex>,
+        children: Vec<I>,
+        add_empty_gchild: bool,
+    ) where
+        I: ToQStringOwned,
+    {
+        if children.is_empty() {
+            return;
+        }
+        let mut model = live_or_default!(self.model());
+        let item = model.item_from_index(idx);
+        model.remove_rows_3a(0, 1, idx);
+        self.set_children(item, children, add_empty_gchild);
+    }
+
+    /// Load the children of the item at the supplied index from the database,
+    /// if they have not been loaded already. The level of the children is
+    /// determined by the hierarchy; by default, children of packages are
+    /// versions, and children of versions are platforms.
+    ///
+    /// # Arguments
+    /// * `idx` - The index of the item whose children should be loaded
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn load_children(&self, idx: Ref<QModelIndex>) {
+        self.load_children_within(idx, usize::MAX);
+    }
+
+    // load the children of the item at the supplied index, as load_children
+    // does, unless there are more than max of them, in which case the item is
+    // left unloaded. Returns the number of children loaded, if they were.
+    fn load_children_within(&self, idx: Ref<QModelIndex>, max: usize) -> Option<usize> {
+        unsafe {
+            let model = live_or_default!(self.model());
+            if !self.needs_children(idx) {
+                return None;
+            }
+            let depth = Self::depth(idx) as usize;
+            let hierarchy = self.hierarchy();
+            let level = match hierarchy.level(depth + 1) {
+                Some(level) => level,
+                // we are the last level (a platform), revealing where the version is pinned
+                None => {
+                    let _loading = self.begin_loading();
+                    self.load_pins(idx);
+                    return Some(model.row_count_1a(idx) as usize);
+                }
+            };
+            let path = self.path(idx);
+            // the placeholder reads "Loading…" until it is replaced by the children
+            self.set_loading_row(idx, &path, true);
+            let _loading = self.begin_loading();
+            let result = self.level_items(level, &path);
+            self.set_loading_row(idx, &path, false);
+            let children = match result {
+                Ok(children) => children,
+                Err(err) => {
+                    log::warn!("Unable to load the children of {:?}: {}", path, err);
+                    self.set_load_error(idx, &err);
+                    return None;
+                }
+            };
+            if children.len() > max {
+                return None;
+            }
+            let count = children.len();
+            self.replace_placeholder(idx, children, self.needs_placeholder(depth + 1));
+            match level {
+                Level::Version if hierarchy.is_default_or_grouped() => {
+                    self.fill_version_columns(idx);
+                    self.annotate_versions(idx);
+                }
+                Level::Platform => self.decorate_platforms(idx),
+                Level::Package => {
+                    // the packages of a group
+                    if hierarchy.is_grouped() {
+                        self.fill_package_rows(&self.child_indexes(idx));
+                        if self.comparison.borrow().is_some() {
+                            self.annotate_package_rows(idx);
+                        }
+                    }
+                    self.apply_filter();
+                }
+                _ => (),
+            }
+            Some(count)
+        }
+    }
+
+    /// Load the children of the item at the supplied index, as load_children
+    /// does, once its placeholder reads "Loading…" on screen. The load is
+    /// deferred until control returns to the event loop, which paints the
+    /// indicators first, as the query blocks the gui thread while it runs.
+    ///
+    /// # Arguments
+    /// * `idx` - The index of the item whose children should be loaded
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn request_children(&self, idx: Ref<QModelIndex>) {
+        let path = unsafe {
+            if !self.needs_children(idx) {
+                return;
+            }
+            let path = self.path(idx);
+            if self.loading_rows.borrow().contains(&path) {
+                return;
+            }
+            self.set_loading_row(idx, &path, true);
+            path
+        };
+        self.defer_load(DeferredLoad::Children(path));
+    }
+
+    /// Update the tree for the current site, as update_site does, once the
+    /// busy indicator is on screen
+    pub(crate) fn request_site_update(&self) {
+        if !self.deferred.borrow().contains(&DeferredLoad::Site) {
+            self.defer_load(DeferredLoad::Site);
+        }
+    }
+
+    // queue a load, which is underway from now until it has run
+    fn defer_load(&self, load: DeferredLoad) {
+        self.start_loading();
+        self.deferred.borrow_mut().push_back(load);
+        match self.deferred_timer.get() {
+            Ok(mut timer) => unsafe { timer.start_0a() },
+            Err(_) => self.run_deferred_load(),
+        }
+    }
+
+    /// Run the next of the deferred loads. The rest are deferred again, so
+    /// that the indicators animate between them.
+    pub(crate) fn run_deferred_load(&self) {
+        let load = match self.deferred.borrow_mut().pop_front() {
+            Some(load) => load,
+            None => return,
+        };
+        match load {
+            DeferredLoad::Children(path) => unsafe {
+                match self.index_from_path(&path) {
+                    Some(idx) => {
+                        // restore the empty placeholder, which marks the
+                        // children as needing to be loaded
+                        self.set_loading_row(idx.as_ref(), &path, false);
+                        self.load_children(idx.as_ref());
+                    }
+                    // the item was removed in the meantime
+                    None => self.loading_rows.borrow_mut().retain(|row| *row != path),
+                }
+            },
+            DeferredLoad::Site => self.update_site(),
+        }
+        self.end_loading();
+        if self.deferred.borrow().is_empty() {
+            return;
+        }
+        match self.deferred_timer.get() {
+            Ok(mut timer) => unsafe { timer.start_0a() },
+            Err(_) => self.run_deferred_load(),
+        }
+    }
+
+    /// Retrieve the timer which runs the deferred loads
+    pub(crate) fn deferred_timer(&self) -> Result<MutPtr<QTimer>, WidgetError> {
+        self.deferred_timer.get()
+    }
+
+    /// Note the start of a load, displaying the animated busy indicator in
+    /// the header, and emitting `loading_changed` if nothing else was
+    /// loading. The load ends when the returned guard is dropped. No nested
+    /// event loop is run, as it would dispatch timers and queued signals in
+    /// the middle of the load, so the indicators are only painted for loads
+    /// deferred via request_children and request_site_update.
+    pub(crate) fn begin_loading(&self) -> LoadingGuard<'_> {
+        self.start_loading();
+        LoadingGuard { tree: self }
+    }
+
+    // note the start of a load, which must be matched by a call to end_loading
+    fn start_loading(&self) {
+        let busy = self.busy.get();
+        self.busy.set(busy + 1);
+        if busy > 0 {
+            return;
+        }
+        self.loading_changed.emit(&true);
+        if let Ok(mut busy_label) = self.busy_label.get() {
+            unsafe {
+                busy_label.set_text(&qs(spinner_text(self.spinner_frame.get())));
+                busy_label.set_visible(true);
+            }
+        }
+        if let Ok(mut spinner_timer) = self.spinner_timer.get() {
+            unsafe { spinner_timer.start_0a() };
+        }
+    }
+
+    // note the end of a load, hiding the busy indicator and emitting
+    // `loading_changed` once nothing is loading
+    fn end_loading(&self) {
+        let busy = self.busy.get().saturating_sub(1);
+        self.busy.set(busy);
+        if busy > 0 {
+            return;
+        }
+        if let Ok(mut spinner_timer) = self.spinner_timer.get() {
+            unsafe { spinner_timer.stop() };
+        }
+        if let Ok(mut busy_label) = self.busy_label.get() {
+            unsafe {
+                busy_label.set_visible(false);
+            }
+        }
+        self.loading_changed.emit(&false);
+    }
+
+    /// Determine whether anything is loading
+    pub(crate) fn is_loading(&self) -> bool {
+        self.busy.get() > 0
+    }
+
+    /// Retrieve the callbacks invoked with true when loading starts, and
+    /// with false once it finishes
+    pub(crate) fn loading_changed(&self) -> &Callbacks<bool> {
+        &self.loading_changed
+    }
+
+    /// Retrieve the timer which animates the loading indicators
+    pub(crate) fn spinner_timer(&self) -> Result<MutPtr<QTimer>, WidgetError> {
+        self.spinner_timer.get()
+    }
+
+    /// Advance the animated loading indicators by a frame. The indicators
+    /// advance whenever control returns to the event loop, which it does
+    /// between deferred loads, but not while a query runs.
+    pub(crate) fn advance_spinner(&self) {
+        let frame = self.spinner_frame.get().wrapping_add(1);
+        self.spinner_frame.set(frame);
+        let text = qs(spinner_text(frame));
+        unsafe {
+            if let Ok(mut busy_label) = self.busy_label.get() {
+                busy_label.set_text(&text);
+            }
+            let model = live_or_default!(self.model());
+            for path in self.loading_rows.borrow().iter() {
+                if let Some(idx) = self.index_from_path(path) {
+                    let placeholder = model.index_3a(0, 0, &idx);
+                    if placeholder.is_valid() {
+                        model.item_from_index(&placeholder).set_text(&text);
+                    }
+                }
+            }
+        }
+    }
+
+    // the index of the item with the supplied path, if it is present
+    unsafe fn index_from_path(&self, path: &[String]) -> Option<CppBox<QModelIndex>> {
+        let model = live_or_default!(self.model());
+        let mut idx = QModelIndex::new();
+        for name in path {
+            let row = (0..model.row_count_1a(&idx)).find(|row| {
+                let child = model.index_3a(*row, 0, &idx);
+                Self::item_name(model.item_from_index(&child)) == *name
+            })?;
+            idx = model.index_3a(row, 0, &idx);
+        }
+        if idx.is_valid() {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+
+    // display the animated loading indicator in the placeholder child of the
+    // item at the supplied index, with the supplied path, while its children
+    // are fetched, or restore the empty placeholder
+    unsafe fn set_loading_row(&self, idx: Ref<QModelIndex>, path: &[String], loading: bool) {
+        let model = live_or_default!(self.model());
+        let placeholder = model.index_3a(0, 0, idx);
+        let loading = loading && placeholder.is_valid();
+        if placeholder.is_valid() {
+            let mut item = model.item_from_index(&placeholder);
+            if loading {
+                // the error of an earlier attempt, if any, gives way to the indicator
+                item.set_data_2a(&QVariant::new(), LOAD_ERROR_ROLE);
+                item.set_tool_tip(&qs(""));
+                item.set_enabled(true);
+                item.set_text(&qs(spinner_text(self.spinner_frame.get())));
+            } else {
+                item.set_text(&qs(""));
+            }
+        }
+        let mut rows = self.loading_rows.borrow_mut();
+        rows.retain(|row| row.as_slice() != path);
+        if loading {
+            rows.push(path.to_vec());
+        }
+    }
+
+    // display the error of a failed load in the placeholder child of the
+    // item at the supplied index, leaving the rest of the tree alone. The
+    // item remains expandable, and expanding it again retries the load.
+    unsafe fn set_load_error(&self, idx: Ref<QModelIndex>, err: &BackendError) {
+        let model = live_or_default!(self.model());
+        let placeholder = model.index_3a(0, 0, idx);
+        if !placeholder.is_valid() {
+            return;
+        }
+        let mut item = model.item_from_index(&placeholder);
+        item.set_text(&qs(format!("Unable to load: {}", err)));
+        item.set_tool_tip(&qs(format!("{}\n\nCollapse and expand to retry", err)));
+        item.set_data_2a(&QVariant::from_bool(true), LOAD_ERROR_ROLE);
+        item.set_enabled(false);
+    }
+
+    /// Retrieve the depth of the supplied index. In the default hierarchy,
+    /// packages are at depth 0, versions at depth 1, platforms at depth 2
+    /// and pins at depth 3.
+    pub(crate) unsafe fn depth(idx: Ref<QModelIndex>) -> i32 {
+        let mut depth = 0;
+        let mut parent = idx.parent();
+        while parent.is_valid() {
+            depth += 1;
+            parent = parent.parent();
+        }
+        depth
+    }
+
+    /// Replace the hierarchy describing the levels of the tree, clearing the
+    /// extant items. Reload them via `load_packages` (or `set_packages`, which
+    /// sets the top level items).
+    pub(crate) fn set_hierarchy(&self, hierarchy: Hierarchy) {
+        *self.hierarchy.borrow_mut() = hierarchy;
+        self.clear();
+    }
+
+    /// Retrieve a copy of the hierarchy
+    pub(crate) fn hierarchy(&self) -> Hierarchy {
+        self.hierarchy.borrow().clone()
+    }
+
+    /// Bucket the top level packages into collapsible groups, or display
+    /// them ungrouped when None, preserving the extant packages. Grouping
+    /// adds the Group level to the top of the hierarchy, which must start
+    /// with the Package level.
+    ///
+    /// # Arguments
+    /// * `grouping` - How packages are grouped, if at all
+    ///
+    /// # Returns
+    /// * Ok(()), or an error if the hierarchy cannot be grouped or the
+    ///   metadata used to group the packages cannot be queried
+    pub(crate) fn set_grouping(&self, grouping: Option<Grouping>) -> Result<(), BackendError> {
+        let hierarchy = match grouping {
+            Some(_) => self.hierarchy().grouped()?,
+            None => self.hierarchy().ungrouped(),
+        };
+        let packages = self.grouped_packages();
+        *self.grouping.borrow_mut() = grouping;
+        self.set_hierarchy(hierarchy);
+        if self.hierarchy.borrow().is_grouped() {
+            self.set_groups(packages)
+        } else {
+            self.set_packages(packages);
+            Ok(())
+        }
+    }
+
+    /// Retrieve a copy of the grouping, if packages are grouped
+    pub(crate) fn grouping(&self) -> Option<Grouping> {
+        self.grouping.borrow().clone()
+    }
+
+    /// Retrieve the groups, along with the packages within each
+    pub(crate) fn groups(&self) -> BTreeMap<String, Vec<String>> {
+        self.groups.borrow().clone()
+    }
+
+    /// Retrieve the top level packages, whether or not they are grouped. If
+    /// the hierarchy does not start with packages, there are none.
+    pub(crate) fn grouped_packages(&self) -> Vec<String> {
+        match self.root_level() {
+            Level::Group => self.groups.borrow().values().flatten().cloned().collect(),
+            Level::Package => unsafe {
+                let model = live_or_default!(self.model());
+                (0..model.row_count_0a())
+                    .map(|row| Self::item_name(model.item_2a(row, 0)))
+                    .collect()
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    // add packages to the extant groups, logging rather than propagating
+    // failures to query the metadata used to group them
+    fn add_to_groups(&self, packages: Vec<String>) {
+        let mut all = self.grouped_packages();
+        all.extend(packages);
+        if let Err(err) = self.set_groups(all) {
+            log::warn!("Unable to group the packages: {}", err);
+        }
+    }
+
+    // the packages pinned at the current site, if the grouping requires them
+    fn pinned_packages(&self, grouping: &Grouping) -> Result<HashSet<String>, BackendError> {
+        if !grouping.requires_pins() {
+            return Ok(HashSet::new());
+        }
+        let site = self.current_site();
+        let pins = self.backend.borrow_mut().site_pins(&site)?;
+        Ok(pins.into_iter().map(|(package, _)| package).collect())
+    }
+
+    // bucket the supplied packages into groups, replacing the extant group
+    // rows. Without a grouping, every package belongs to the Other group.
+    fn set_groups(&self, packages: Vec<String>) -> Result<(), BackendError> {
+        let groups = match self.grouping.borrow().as_ref() {
+            Some(grouping) if !packages.is_empty() => {
+                grouping.group(&packages, &self.pinned_packages(grouping)?)
+            }
+            Some(_) => BTreeMap::new(),
+            None => {
+                let mut groups = BTreeMap::new();
+                if !packages.is_empty() {
+                    groups.insert(OTHER_GROUP.to_string(), packages);
+                }
+                groups
+            }
+        };
+        let model = self.model()?;
+        unsafe {
+            let root = model.invisible_root_item();
+            let names = groups.keys().cloned().collect::<Vec<_>>();
+            let no_icon = QIcon::new();
+            self.sync_rows(root, &names, |name| {
+                let mut item = self.new_item(name, no_icon.as_ref(), 0);
+                // the text includes the count, so the name is kept under the UserRole
+                item.set_data_2a(
+                    &QVariant::from_q_string(&qs(name)),
+                    ItemDataRole::UserRole.to_int(),
+                );
+                item
+            });
+            // update the counts, along with the packages of the groups which
+            // have been loaded, leaving those which remain intact
+            let icon = self.level_icon(Level::Package);
+            for row in 0..root.row_count() {
+                let mut item = root.child_2a(row, 0);
+                let name = Self::item_name(item);
+                let members = match groups.get(&name) {
+                    Some(members) => members,
+                    None => continue,
+                };
+                item.set_text(&qs(format!("{} ({})", name, members.len())));
+                if !self.needs_children(model.index_2a(row, 0).as_ref()) {
+                    self.sync_rows(item, members, |package| {
+                        self.new_item(package, icon.as_ref(), 1)
+                    });
+                }
+            }
+        }
+        *self.groups.borrow_mut() = groups;
+        self.apply_filter();
+        Ok(())
+    }
+
+    // the level of the top level items
+    fn root_level(&self) -> Level {
+        self.hierarchy
+            .borrow()
+            .level(0)
+            .expect("a hierarchy always has a level")
+    }
+
+    // whether the packages are the top level items, or are bucketed into groups
+    fn packages_at_top(&self) -> bool {
+        match self.root_level() {
+            Level::Package | Level::Group => true,
+            _ => false,
+        }
+    }
+
+    // whether the items at the supplied depth require a placeholder child, in
+    // order to be expandable
+    fn needs_placeholder(&self, depth: usize) -> bool {
+        let hierarchy = self.hierarchy.borrow();
+        depth + 1 < hierarchy.levels().len() || (self.show_pins() && hierarchy.supports_pins())
+    }
+
+    // the icon displayed next to the items of the supplied level
+    unsafe fn level_icon(&self, level: Level) -> CppBox<QIcon> {
+        match level {
+            Level::Site => QIcon::from_q_string(&qs(self.icons().site())),
+            Level::Package => QIcon::from_q_string(&qs(self.icons().package())),
+            // platforms are decorated by the platform registry
+            Level::Group | Level::Version | Level::Platform => QIcon::new(),
+        }
+    }
+
+    // query the items of a level, given the names of the ancestors of the
+    // items, which determine the package of versions, and restrict the
+    // packages and versions beneath a site or platform to those pinned there
+    fn level_items(&self, level: Level, path: &[String]) -> Result<Vec<String>, BackendError> {
+        let mut backend = self.backend.borrow_mut();
+        match level {
+            Level::Site => backend.sites(),
+            Level::Group => Ok(self.groups.borrow().keys().cloned().collect()),
+            // the packages of a group are those bucketed into it
+            Level::Package => {
+                let packages = match path.first() {
+                    Some(group) if self.hierarchy.borrow().is_grouped() => {
+                        self.groups.borrow().get(group).cloned().unwrap_or_default()
+                    }
+                    _ => backend.packages()?,
+                };
+                Ok(match self.ancestor_pins(&mut backend, path)? {
+                    Some(pins) => {
+                        let pinned = pins
+                            .iter()
+                            .map(|(package, _)| package)
+                            .collect::<HashSet<_>>();
+                        packages
+                            .into_iter()
+                            .filter(|package| pinned.contains(package))
+                            .collect()
+                    }
+                    None => packages,
+                })
+            }
+            Level::Platform => backend.platforms(),
+            Level::Version => {
+                let package = self
+                    .hierarchy
+                    .borrow()
+                    .depth_of(Level::Package)
+                    .and_then(|depth| path.get(depth))
+                    .cloned()
+                    .ok_or("versions must be beneath a package")?;
+                let versions = backend.versions(&package)?;
+                Ok(match self.ancestor_pins(&mut backend, path)? {
+                    Some(pins) => {
+                        let pinned = pins
+                            .iter()
+                            .filter(|(pinned_package, _)| *pinned_package == package)
+                            .map(|(_, pin)| pin.version.as_str())
+                            .collect::<HashSet<_>>();
+                        versions
+                            .into_iter()
+                            .filter(|version| pinned.contains(version.as_str()))
+                            .collect()
+                    }
+                    None => versions,
+                })
+            }
+        }
+    }
+
+    // The (package, pin) pairs in effect beneath the Site and Platform
+    // ancestors in path, which restrict the packages and versions listed
+    // beneath them. Without a Site ancestor, the current site applies. A pin
+    // on the `any` platform applies to every platform. None if path has
+    // neither ancestor.
+    fn ancestor_pins(
+        &self,
+        backend: &mut Backend,
+        path: &[String],
+    ) -> Result<Option<Vec<(String, PinLocation)>>, BackendError> {
+        let (site, platform) = {
+            let hierarchy = self.hierarchy.borrow();
+            let ancestor = |level| {
+                hierarchy
+                    .depth_of(level)
+                    .and_then(|depth| path.get(depth))
+                    .cloned()
+            };
+            (ancestor(Level::Site), ancestor(Level::Platform))
+        };
+        if site.is_none() && platform.is_none() {
+            return Ok(None);
+        }
+        let site = site.unwrap_or_else(|| self.current_site());
+        let pins = backend
+            .site_pins(&site)?
+            .into_iter()
+            .filter(|(_, pin)| match &platform {
+                Some(platform) => pin.platform == *platform || pin.platform == "any",
+                None => true,
+            })
+            .collect();
+        Ok(Some(pins))
+    }
+
+    // the names of the supplied index and its ancestors, top level first
+    unsafe fn path(&self, idx: Ref<QModelIndex>) -> Vec<String> {
+        let model = live_or_default!(self.model());
+        let mut names = Vec::new();
+        let mut current = model.index_3a(idx.row(), 0, idx.parent().as_ref());
+        while current.is_valid() {
+            names.push(Self::item_name(model.item_from_index(current.as_ref())));
+            current = current.parent();
+        }
+        names.reverse();
+        names
+    }
+
+    // the indexes of the loaded items at the supplied depth, skipping the
+    // placeholders of items whose children have yet to be loaded
+    unsafe fn loaded_indexes(&self, depth: usize) -> Vec<CppBox<QModelIndex>> {
+        let model = live_or_default!(self.model());
+        let mut indexes = vec![QModelIndex::new()];
+        for _ in 0..=depth {
+            let mut children = Vec::new();
+            for parent in &indexes {
+                if parent.is_valid() && self.needs_children(parent.as_ref()) {
+                    continue;
+                }
+                for row in 0..model.row_count_1a(parent) {
+                    children.push(model.index_3a(row, 0, parent));
+                }
+            }
+            indexes = children;
+        }
+        indexes
+    }
+
+    /// Show or hide the pins beneath each platform. When shown, platforms may
+    /// be expanded to list the role and level at which the version is pinned
+    /// for the platform at the current site. Pins are only listed when the
+    /// last level of the hierarchy is the platform.
+    pub(crate) fn set_show_pins(&self, show: bool) {
+        if self.show_pins.replace(show) == show {
+            return;
+        }
+        let hierarchy = self.hierarchy();
+        if !hierarchy.supports_pins() {
+            return;
+        }
+        unsafe {
+            let mut model = live_or_default!(self.model());
+            for platform_idx in self.loaded_indexes(hierarchy.levels().len() - 1) {
+                let mut platform_item = model.item_from_index(&platform_idx);
+                let child_count = model.row_count_1a(&platform_idx);
+                if show && child_count == 0 {
+                    let mut placeholder = QStandardItem::new();
+                    placeholder.set_text(&qs(""));
+                    placeholder.set_editable(false);
+                    platform_item.append_row_q_standard_item(placeholder.into_ptr());
+                } else if !show && child_count > 0 {
+                    model.remove_rows_3a(0, child_count, &platform_idx);
+                }
+            }
+        }
+    }
+
+    /// Compare the pins of two of the sites in the site combobox. The
+    /// differences are listed side by side in a table beside the tree, with a
+    /// column of versions for each site. Packages whose pins differ are
+    /// highlighted, and the differences are listed in the tooltips of the
+    /// packages and of the versions involved.
+    ///
+    /// # Arguments
+    /// * `first` - The name of the first site
+    /// * `second` - The name of the second site
+    ///
+    /// # Returns
+    /// * Ok(()) or an error if either site is unknown or the query fails
+    pub(crate) fn compare_sites(&self, first: &str, second: &str) -> Result<(), BackendError> {
+        let sites = self.sites();
+        for site in &[first, second] {
+            if !sites.iter().any(|s| s == site) {
+                return Err(format!("{} is not one of the available sites", site).into());
+            }
+        }
+        let first_pins = self.backend.borrow_mut().site_pins(first)?;
+        let second_pins = self.backend.borrow_mut().site_pins(second)?;
+        let comparison = SiteComparison::new(first, &first_pins, second, &second_pins);
+        unsafe {
+            self.fill_comparison_table(&comparison)?;
+        }
+        *self.comparison.borrow_mut() = Some(comparison);
+        self.annotate_packages();
+        self.apply_filter();
+        Ok(())
+    }
+
+    /// Leave comparison mode, removing the highlights and the side by side table
+    pub(crate) fn end_comparison(&self) {
+        self.comparison.borrow_mut().take();
+        if let Ok(mut table) = self.comparison_table() {
+            unsafe {
+                table.clear_contents();
+                table.set_row_count(0);
+                table.set_visible(false);
+            }
+        }
+        self.annotate_packages();
+        self.apply_filter();
+    }
+
+    // list the differences of the comparison in the side by side table,
+    // revealing it
+    unsafe fn fill_comparison_table(&self, comparison: &SiteComparison) -> Result<(), WidgetError> {
+        let mut table = self.comparison_table()?;
+        let mut labels = QStringList::new();
+        for label in comparison.columns().iter() {
+            labels.append_q_string(&qs(label));
+        }
+        table.set_horizontal_header_labels(&labels);
+        table.clear_contents();
+        let rows = comparison.rows();
+        table.set_row_count(rows.len() as i32);
+        for (row, columns) in rows.iter().enumerate() {
+            for (column, text) in columns.iter().enumerate() {
+                let item = QTableWidgetItem::from_q_string(&qs(text));
+                table.set_item(row as i32, column as i32, item.into_ptr());
+            }
+        }
+        table.resize_columns_to_contents();
+        table.set_visible(true);
+        Ok(())
+    }
+
+    /// Retrieve the table listing the differences of the comparison side by side
+    pub(crate) fn comparison_table(&self) -> Result<MutPtr<QTableWidget>, WidgetError> {
+        self.comparison_table.get()
+    }
+
+    /// Select the version of the difference at the supplied row of the side by
+    /// side table in the tree, preferring the version at the first site
+    pub(crate) fn reveal_comparison_entry(&self, row: i32) {
+        let columns = match self.comparison.borrow().as_ref() {
+            Some(comparison) => match comparison.rows().get(row as usize) {
+                Some(columns) => columns.clone(),
+                None => return,
+            },
+            None => return,
+        };
+        let [package, _, _, first, second] = columns;
+        let version = if first.is_empty() { second } else { first };
+        if !self.reveal_version(&package, &version) {
+            log::warn!("{}-{} is not in the tree", package, version);
+        }
+    }
+
+    /// Retrieve the current comparison, if any
+    pub(crate) fn comparison(&self) -> Option<SiteComparison> {
+        self.comparison.borrow().clone()
+    }
+
+    /// When comparing sites, show only the packages whose pins differ
+    pub(crate) fn set_show_only_differences(&self, only: bool) {
+        self.only_differences.set(only);
+        self.apply_filter();
+    }
+
+    /// Retrieve whether only the packages which differ are shown when comparing sites
+    pub(crate) fn show_only_differences(&self) -> bool {
+        self.only_differences.get()
+    }
+
+    /// Retrieve the sites in the site combobox
+    pub(crate) fn sites(&self) -> Vec<String> {
+        let cbox = live_or_default!(self.combobox());
+        unsafe {
+            (0..cbox.count())
+                .map(|idx| cbox.item_text(idx).to_std_string())
+                .collect()
+        }
+    }
+
+    /// Set how the filter text is matched against package names, reapplying the filter
+    pub(crate) fn set_filter_mode(&self, mode: FilterMode) {
+        self.filter_mode.set(mode);
+        self.apply_filter();
+    }
+
+    /// Retrieve how the filter text is matched against package names
+    pub(crate) fn filter_mode(&self) -> FilterMode {
+        self.filter_mode.get()
+    }
+
+    /// Hide the packages which do not match the filter text, along with those
+    /// which do not differ when comparing sites with only differences shown.
+    /// Packages are filtered at whichever level of the hierarchy they appear,
+    /// and groups without any visible packages are hidden. If nothing remains,
+    /// the appropriate overlay is displayed in place of the view.
+    pub(crate) fn apply_filter(&self) {
+        self.filter_rows();
+        self.update_overlay();
+    }
+
+    // hide the rows which do not match the filter, counting the matches
+    fn filter_rows(&self) {
+        unsafe {
+            let model = live_or_default!(self.model());
+            let mut view = live_or_default!(self.view());
+            let filter = live_or_default!(self.filter()).text().to_std_string();
+            let is_visible = |name: &str| self.package_matches(name, &filter);
+            let depth = match self.hierarchy.borrow().depth_of(Level::Package) {
+                Some(depth) => depth,
+                None => return,
+            };
+            let mut count = MatchCount::default();
+            for idx in self.loaded_indexes(depth) {
+                count.total += 1;
+                if self.filter_package(idx.as_ref(), &filter) {
+                    count.matched += 1;
+                }
+            }
+            // groups are hidden when none of their packages are visible,
+            // whether or not the packages have been loaded
+            if self.hierarchy.borrow().is_grouped() {
+                let root = QModelIndex::new();
+                let groups = self.groups.borrow();
+                for row in 0..model.row_count_0a() {
+                    let name = Self::item_name(model.item_2a(row, 0));
+                    let visible = groups
+                        .get(&name)
+                        .map(|packages| packages.iter().any(|package| is_visible(package)))
+                        .unwrap_or(false);
+                    view.set_row_hidden(row, root.as_ref(), !visible);
+                }
+                // count every package, rather than those which have been loaded
+                let packages = groups.values().flatten().collect::<Vec<_>>();
+                count.total = packages.len();
+                count.matched = packages.iter().filter(|p| is_visible(p)).count();
+            }
+            self.set_match_count(count);
+        }
+    }
+
+    // determine whether the named package passes the filter, and, when only
+    // the differences between two sites are shown, differs between them
+    fn package_matches(&self, name: &str, filter: &str) -> bool {
+        let mode = self.filter_mode.get();
+        match (
+            self.comparison.borrow().as_ref(),
+            self.only_differences.get(),
+        ) {
+            (Some(comparison), true) => mode.matches(name, filter) && comparison.differs(name),
+            _ => mode.matches(name, filter),
+        }
+    }
+
+    // hide the package at the supplied index unless it matches the filter,
+    // highlighting the match if it does. Returns whether it matches.
+    unsafe fn filter_package(&self, idx: Ref<QModelIndex>, filter: &str) -> bool {
+        let name = Self::item_name(live_or_default!(self.model()).item_from_index(idx));
+        let visible = self.package_matches(&name, filter);
+        let mut view = live_or_default!(self.view());
+        view.set_row_hidden(idx.row(), &idx.parent(), !visible);
+        let html = if visible && self.highlight_matches.get() {
+            let color = self.highlight_color.borrow();
+            self.filter_mode.get().highlight(&name, filter, &color)
+        } else {
+            None
+        };
+        self.highlight_row(idx, html);
+        visible
+    }
+
+    // record the rich text of the package at the supplied index under the
+    // HIGHLIGHT_ROLE, which the view's highlight delegate draws in place of
+    // its text, or clear it when there is nothing to highlight. The item is
+    // only touched when the highlight changes, as every change repaints it.
+    unsafe fn highlight_row(&self, idx: Ref<QModelIndex>, html: Option<String>) {
+        let mut item = live_or_default!(self.model()).item_from_index(idx);
+        let current = item.data_1a(HIGHLIGHT_ROLE);
+        match html {
+            Some(html) => {
+                if current.to_string().to_std_string() != html {
+                    item.set_data_2a(&QVariant::from_q_string(&qs(html)), HIGHLIGHT_ROLE);
+                }
+            }
+            None => {
+                if current.is_valid() {
+                    item.set_data_2a(&QVariant::new(), HIGHLIGHT_ROLE);
+                }
+            }
+        }
+    }
+
+    // set the foreground of an item: the difference brush if annotated by a
+    // comparison, and the default otherwise. The highlight delegate draws
+    // the unmatched text of a highlighted item in the same foreground.
+    unsafe fn refresh_foreground(item: MutPtr<QStandardItem>) {
+        let mut item = item;
+        if item.data_1a(COMPARISON_ROLE).to_bool() {
+            item.set_foreground(&Self::difference_brush());
+        } else {
+            item.set_data_2a(&QVariant::new(), ItemDataRole::ForegroundRole.to_int());
+        }
+    }
+
+    // record the number of matching packages, displaying it beside the filter
+    unsafe fn set_match_count(&self, count: MatchCount) {
+        self.match_count.set(count);
+        let mut label = live_or_default!(self.match_count_label.get());
+        label.set_text(&qs(count.to_string()));
+    }
+
+    /// Retrieve the number of packages matching the filter, out of those
+    /// considered. When packages are grouped, every package is considered;
+    /// otherwise, only those which have been loaded.
+    pub(crate) fn match_count(&self) -> MatchCount {
+        self.match_count.get()
+    }
+
+    /// Enable or disable highlighting the text matching the filter within
+    /// each visible package
+    pub(crate) fn set_highlight_matches(&self, enabled: bool) {
+        self.highlight_matches.set(enabled);
+        self.apply_filter();
+    }
+
+    /// Determine whether the text matching the filter is highlighted
+    pub(crate) fn highlight_matches(&self) -> bool {
+        self.highlight_matches.get()
+    }
+
+    /// Set the background color of highlighted matches, as understood by qt's
+    /// rich text (eg `#b5762a` or `orange`)
+    pub(crate) fn set_highlight_color(&self, color: &str) {
+        *self.highlight_color.borrow_mut() = color.to_string();
+        self.apply_filter();
+    }
+
+    /// Retrieve the background color of highlighted matches
+    pub(crate) fn highlight_color(&self) -> String {
+        self.highlight_color.borrow().clone()
+    }
+
+    // highlight the packages which differ in the current comparison, and
+    // the versions which are involved, clearing the highlights otherwise
+    fn annotate_packages(&self) {
+        unsafe {
+            for parent in self.package_parents() {
+                self.annotate_package_rows(parent.as_ref());
+            }
+        }
+    }
+
+    // annotate the packages beneath the supplied parent (the root, or a group)
+    unsafe fn annotate_package_rows(&self, parent: Ref<QModelIndex>) {
+        let model = live_or_default!(self.model());
+        for row in 0..model.row_count_1a(parent) {
+            let idx = model.index_3a(row, 0, parent);
+            self.annotate_package(model.item_from_index(&idx));
+            self.annotate_versions(idx.as_ref());
+        }
+    }
+
+    // annotate a package which differs in the current comparison
+    unsafe fn annotate_package(&self, item: MutPtr<QStandardItem>) {
+        let name = Self::item_name(item);
+        match self.comparison.borrow().as_ref() {
+            Some(comparison) if comparison.differs(&name) => {
+                Self::annotate_item(item, &comparison.summary(&name));
+            }
+            _ => Self::clear_annotation(item),
+        }
+    }
+
+    /// Highlight the loaded versions of the package at the supplied index which
+    /// are involved in a difference in the current comparison
+    pub(crate) unsafe fn annotate_versions(&self, idx: Ref<QModelIndex>) {
+        if !self.hierarchy.borrow().is_default_or_grouped() || self.needs_children(idx) {
+            return;
+        }
+        let package_item = live_or_default!(self.model()).item_from_index(idx);
+        let package = package_item.text().to_std_string();
+        let comparison = self.comparison.borrow();
+        for row in 0..package_item.row_count() {
+            let mut version_item = package_item.child_2a(row, 0);
+            let version = version_item.text().to_std_string();
+            let descriptions = comparison
+                .as_ref()
+                .map(|comparison| {
+                    comparison
+                        .differences(&package)
+                        .iter()
+                        .filter(|difference| difference.involves(&version))
+                        .map(|difference| difference.describe())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            if descriptions.is_empty() {
+                Self::clear_annotation(version_item);
+            } else {
+                Self::annotate_item(version_item, &descriptions.join("\n"));
+            }
+        }
+    }
+
+    // highlight an item involved in a difference, describing the difference
+    // in its tooltip. The item is marked under the COMPARISON_ROLE so that
+    // clear_annotation leaves the highlights and tooltips of others alone.
+    unsafe fn annotate_item(item: MutPtr<QStandardItem>, tooltip: &str) {
+        let mut item = item;
+        item.set_tool_tip(&qs(tooltip));
+        item.set_data_2a(&QVariant::from_bool(true), COMPARISON_ROLE);
+        Self::refresh_foreground(item);
+    }
+
+    // remove the highlight and tooltip set by annotate_item, if any
+    unsafe fn clear_annotation(item: MutPtr<QStandardItem>) {
+        let mut item = item;
+        if !item.data_1a(COMPARISON_ROLE).to_bool() {
+            return;
+        }
+        item.set_data_2a(&QVariant::new(), ItemDataRole::ToolTipRole.to_int());
+        item.set_data_2a(&QVariant::new(), COMPARISON_ROLE);
+        Self::refresh_foreground(item);
+    }
+
+    // the brush used to highlight differences
+    unsafe fn difference_brush() -> CppBox<QBrush> {
+        QBrush::from_q_color(&QColor::from_rgb_3a(230, 150, 40))
+    }
+
+    /// Present the context menu for the item at the supplied position (in
+    /// viewport coordinates). Every row offers to show the pin history of its
+    /// package, and in edit mode, versions and platforms offer to create a pin.
+    pub(crate) fn show_context_menu(&self, pos: Ref<QPoint>) {
+        unsafe {
+            let view = live_or_default!(self.view());
+            let idx = view.index_at(pos);
+            if !idx.is_valid() {
+                return;
+            }
+            let selection = self.selection_from_index(idx.as_ref());
+            let mut menu = QMenu::new();
+            // rows above the packages (eg groups or sites) lack package actions
+            let history_action = if selection.package.is_empty() {
+                None
+            } else {
+                Some(menu.add_action_q_string(&qs("Show History")))
+            };
+            // versions and platforms may be pinned in edit mode
+            let pin_action = if self.edit_mode() && selection.version.is_some() {
+                Some(menu.add_action_q_string(&qs("Create Pin...")))
+            } else {
+                None
+            };
+            if menu.actions().is_empty() {
+                return;
+            }
+            let global_pos = view.viewport().map_to_global(pos);
+            let chosen = menu.exec_1a_mut(&global_pos);
+            if chosen.is_null() {
+                return;
+            }
+            if history_action.map(|action| action.as_raw_ptr()) == Some(chosen.as_raw_ptr()) {
+                if let Err(err) = self.show_history(&selection.package) {
+                    log::warn!(
+                        "unable to retrieve the history of {}: {}",
+                        selection.package,
+                        err
+                    );
+                }
+            } else if pin_action.map(|action| action.as_raw_ptr()) == Some(chosen.as_raw_ptr()) {
+                self.request_pin(&selection);
+            }
+        }
+    }
+
+    /// Enable or disable edit mode, in which versions and platforms may be
+    /// pinned from the context menu
+    pub(crate) fn set_edit_mode(&self, enabled: bool) {
+        self.edit_mode.set(enabled);
+    }
+
+    /// Retrieve whether edit mode is enabled
+    pub(crate) fn edit_mode(&self) -> bool {
+        self.edit_mode.get()
+    }
+
+    /// Retrieve the callbacks invoked with each pin created through the tree
+    pub(crate) fn pin_created(&self) -> &Callbacks<PinPreview> {
+        &self.pin_created
+    }
+
+    /// Validate a pin request and preview its outcome without writing it
+    pub(crate) fn preview_pin(&self, request: &PinRequest) -> Result<PinPreview, BackendError> {
+        self.backend.borrow_mut().preview_pin(request)
+    }
+
+    /// Validate and write a pin request, updating the pin markers of its
+    /// package and invoking the pin_created callbacks
+    ///
+    /// # Arguments
+    /// * `request` - The PinRequest
+    /// * `comment` - The comment recorded with the change
+    ///
+    /// # Returns
+    /// * Result wrapping the PinPreview of the written pin, or an error
+    pub(crate) fn create_pin(
+        &self,
+        request: &PinRequest,
+        comment: &str,
+    ) -> Result<PinPreview, BackendError> {
+        let preview = self.preview_pin(request)?;
+        if preview.is_noop() {
+            return Err(preview.describe().into());
+        }
+        let author = self.require_author()?;
+        self.backend.borrow_mut().create_pin(
+            request,
+            preview.replaces.is_some(),
+            &author,
+            comment,
+        )?;
+        self.refresh_pins(&request.package);
+        self.push_change(PinChange::new(request.clone(), preview.replaces.clone()));
+        self.pin_created.emit(&preview);
+        Ok(preview)
+    }
+
+    /// Set the user recorded as having made the pin changes written through the tree
+    pub(crate) fn set_author(&self, author: &str) {
+        *self.author.borrow_mut() = Some(author.to_string());
+    }
+
+    /// Retrieve the user recorded as having made pin changes, if one has been set
+    pub(crate) fn author(&self) -> Option<String> {
+        self.author.borrow().clone()
+    }
+
+    // the user recorded as having made pin changes, which must have been set
+    fn require_author(&self) -> Result<String, BackendError> {
+        self.author()
+            .ok_or_else(|| "no author has been set for pin changes; see set_author".into())
+    }
+
+    // record a change on the undo stack
+    fn push_change(&self, change: PinChange) {
+        self.undo_stack.borrow_mut().push(change);
+        self.undo_stack_changed.emit(&self.undo_stack());
+    }
+
+    /// Retrieve a copy of the undo stack
+    pub(crate) fn undo_stack(&self) -> UndoStack {
+        self.undo_stack.borrow().clone()
+    }
+
+    /// Retrieve the callbacks invoked with the undo stack whenever it changes
+    pub(crate) fn undo_stack_changed(&self) -> &Callbacks<UndoStack> {
+        &self.undo_stack_changed
+    }
+
+    /// Discard the recorded pin changes
+    pub(crate) fn clear_undo_stack(&self) {
+        self.undo_stack.borrow_mut().clear();
+        self.undo_stack_changed.emit(&self.undo_stack());
+    }
+
+    /// Revert the most recent pin change, restoring the version previously
+    /// pinned at its coordinates, or removing the pin if there was none
+    ///
+    /// # Returns
+    /// * Result wrapping the undone change (None if there was nothing to undo), or an error
+    pub(crate) fn undo(&self) -> Result<Option<PinChange>, BackendError> {
+        let change = match self.undo_stack.borrow().next_undo() {
+            Some(change) => change.clone(),
+            None => return Ok(None),
+        };
+        let comment = format!("undo {}", change.describe());
+        let author = self.require_author()?;
+        match change.revert_request() {
+            // the previous version replaces the change's own pin
+            Some(request) => self
+                .backend
+                .borrow_mut()
+                .create_pin(&request, true, &author, &comment)?,
+            None => self
+                .backend
+                .borrow_mut()
+                .remove_pin(&change.request, &author, &comment)?,
+        }
+        self.refresh_pins(&change.request.package);
+        self.undo_stack.borrow_mut().undone();
+        self.undo_stack_changed.emit(&self.undo_stack());
+        Ok(Some(change))
+    }
+
+    /// Reapply the most recently undone pin change
+    ///
+    /// # Returns
+    /// * Result wrapping the redone change (None if there was nothing to redo), or an error
+    pub(crate) fn redo(&self) -> Result<Option<PinChange>, BackendError> {
+        let change = match self.undo_stack.borrow().next_redo() {
+            Some(change) => change.clone(),
+            None => return Ok(None),
+        };
+        let comment = format!("redo {}", change.describe());
+        let author = self.require_author()?;
+        // undoing restored the previous version, if there was one, which the
+        // change replaces once more
+        self.backend.borrow_mut().create_pin(
+            &change.request,
+            change.previous.is_some(),
+            &author,
+            &comment,
+        )?;
+        self.refresh_pins(&change.request.package);
+        self.undo_stack.borrow_mut().redone();
+        self.undo_stack_changed.emit(&self.undo_stack());
+        Ok(Some(change))
+    }
+
+    // present the role and level chooser for the selected version (and
+    // platform), preview the request, and write it once confirmed
+    unsafe fn request_pin(&self, selection: &DistributionSelection) {
+        let (request, comment) = match self.choose_pin(selection) {
+            Some(choice) => choice,
+            None => return,
+        };
+        let parent = live_or_default!(self.widget());
+        let preview = match self.preview_pin(&request) {
+            Ok(preview) if !preview.is_noop() => preview,
+            Ok(preview) => {
+                QMessageBox::information_q_widget2_q_string(
+                    parent,
+                    &qs("Create Pin"),
+                    &qs(preview.describe()),
+                );
+                return;
+            }
+            Err(err) => {
+                QMessageBox::warning_q_widget2_q_string(
+                    parent,
+                    &qs("Create Pin"),
+                    &qs(err.to_string()),
+                );
+                return;
+            }
+        };
+        let confirmed = QMessageBox::question_q_widget2_q_string(
+            parent,
+            &qs("Create Pin"),
+            &qs(format!("{}?", preview.describe())),
+        );
+        if confirmed != q_message_box::StandardButton::Yes {
+            return;
+        }
+        if let Err(err) = self.create_pin(&request, &comment) {
+            QMessageBox::warning_q_widget2_q_string(
+                parent,
+                &qs("Create Pin"),
+                &qs(format!("Unable to create pin: {}", err)),
+            );
+        }
+    }
+
+    // present a modal chooser for the role, level and platform of a pin,
+    // along with a dry run preview which tracks the choices. Returns the
+    // request and comment if accepted.
+    unsafe fn choose_pin(&self, selection: &DistributionSelection) -> Option<(PinRequest, String)> {
+        let version = selection.version.clone()?;
+        let parent = self.widget().ok()?;
+        let (roles, levels, platforms) = {
+            let mut backend = self.backend.borrow_mut();
+            match (backend.roles(), backend.levels(), backend.platforms()) {
+                (Ok(roles), Ok(levels), Ok(platforms)) => (roles, levels, platforms),
+                (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => {
+                    QMessageBox::warning_q_widget2_q_string(
+                        parent,
+                        &qs("Create Pin"),
+                        &qs(format!(
+                            "Unable to retrieve roles, levels and platforms: {}",
+                            err
+                        )),
+                    );
+                    return None;
+                }
+            }
+        };
+        let mut dialog = QDialog::new_1a(parent);
+        dialog.set_object_name(&qs("PinChooser"));
+        dialog.set_window_title(&qs(format!("Pin {}-{}", selection.package, version)));
+        let mut layout = QFormLayout::new_0a();
+        let mut layout_ptr = layout.as_mut_ptr();
+        dialog.set_layout(layout.into_ptr());
+
+        let new_cbox = |items: Vec<String>, current: &str| {
+            let mut cbox = QComboBox::new_0a();
+            for item in items {
+                cbox.add_item_q_string(&qs(item));
+            }
+            cbox.set_current_text(&qs(current));
+            cbox
+        };
+        let mut role_cbox = new_cbox(
+            std::iter::once("any".to_string()).chain(roles).collect(),
+            "any",
+        );
+        let role_ptr = role_cbox.as_mut_ptr();
+        let mut level_cbox = new_cbox(
+            std::iter::once("facility".to_string())
+                .chain(levels)
+                .collect(),
+            "facility",
+        );
+        let level_ptr = level_cbox.as_mut_ptr();
+        let mut platform_cbox = new_cbox(
+            std::iter::once("any".to_string())
+                .chain(platforms)
+                .collect(),
+            selection.platform.as_deref().unwrap_or("any"),
+        );
+        let platform_ptr = platform_cbox.as_mut_ptr();
+        let mut comment = QLineEdit::new();
+        let comment_ptr = comment.as_mut_ptr();
+        comment.set_placeholder_text(&qs("Reason for the change"));
+        let mut preview = QLabel::new();
+        let preview_ptr = preview.as_mut_ptr();
+        preview.set_object_name(&qs("PinPreview"));
+        preview.set_word_wrap(true);
+
+        layout_ptr.add_row_q_string_q_widget(
+            &qs("Site"),
+            QLabel::from_q_string(&qs(&selection.site)).into_ptr(),
+        );
+        layout_ptr.add_row_q_string_q_widget(&qs("Role"), role_cbox.into_ptr());
+        layout_ptr.add_row_q_string_q_widget(&qs("Level"), level_cbox.into_ptr());
+        layout_ptr.add_row_q_string_q_widget(&qs("Platform"), platform_cbox.into_ptr());
+        layout_ptr.add_row_q_string_q_widget(&qs("Comment"), comment.into_ptr());
+        layout_ptr.add_row_q_widget(preview.into_ptr());
+
+        let mut buttons = QDialogButtonBox::from_q_flags_standard_button(
+            StandardButton::Ok | StandardButton::Cancel,
+        );
+        buttons.accepted().connect(dialog.slot_accept());
+        buttons.rejected().connect(dialog.slot_reject());
+        layout_ptr.add_row_q_widget(buttons.into_ptr());
+
+        let make_request = || {
+            PinRequest::new(
+                selection.package.clone(),
+                version.clone(),
+                platform_ptr.current_text().to_std_string(),
+                selection.site.clone(),
+                role_ptr.current_text().to_std_string(),
+                level_ptr.current_text().to_std_string(),
+            )
+        };
+        // the dry run preview, refreshed as the choices change
+        let refresh_preview = || {
+            let text = match self.preview_pin(&make_request()) {
+                Ok(preview) => preview.describe(),
+                Err(err) => err.to_string(),
+            };
+            let mut preview = preview_ptr;
+            preview.set_text(&qs(text));
+        };
+        refresh_preview();
+        let update_preview = SlotOfQString::new(|_text: Ref<QString>| refresh_preview());
+        role_ptr.current_text_changed().connect(&update_preview);
+        level_ptr.current_text_changed().connect(&update_preview);
+        platform_ptr.current_text_changed().connect(&update_preview);
+
+        if dialog.exec() != DialogCode::Accepted.to_int() {
+            return None;
+        }
+        let mut comment = comment_ptr.text().to_std_string();
+        if comment.trim().is_empty() {
+            comment = format!("pinned via {}", env!("CARGO_PKG_NAME"));
+        }
+        Some((make_request(), comment))
+    }
+
+    /// Refresh the pin markers of the loaded versions of a package, along with
+    /// their pinned site counts and any pins listed beneath their platforms
+    pub(crate) fn refresh_pins(&self, package: &str) {
+        unsafe {
+            let mut model = live_or_default!(self.model());
+            let package_idx = match self.find_package(package) {
+                Some(idx) => idx,
+                None => return,
+            };
+            if self.needs_children(package_idx.as_ref()) {
+                return;
+            }
+            let site = self.current_site();
+            let pins = match self.backend.borrow_mut().pins(package, &site) {
+                Ok(pins) => pins,
+                Err(err) => {
+                    log::warn!("Unable to retrieve pins for {}: {}", package, err);
+                    return;
+                }
+            };
+            for version_row in 0..model.row_count_1a(&package_idx) {
+                let version_idx = model.index_3a(version_row, 0, &package_idx);
+                let version = model.item_from_index(&version_idx).text().to_std_string();
+                self.set_pinned(
+                    package,
+                    &version,
+                    pins.iter().any(|pin| pin.version == version),
+                );
+                if !self.show_pins() || self.needs_children(version_idx.as_ref()) {
+                    continue;
+                }
+                for platform_row in 0..model.row_count_1a(&version_idx) {
+                    let platform_idx = model.index_3a(platform_row, 0, &version_idx);
+                    let child_count = model.row_count_1a(&platform_idx);
+                    if child_count == 0 || self.needs_children(platform_idx.as_ref()) {
+                        continue;
+                    }
+                    // swap the loaded pins for a placeholder, and reload them
+                    model.remove_rows_3a(0, child_count, &platform_idx);
+                    model
+                        .item_from_index(&platform_idx)
+                        .append_row_q_standard_item(Self::text_item("").into_ptr());
+                    self.load_pins(platform_idx.as_ref());
+                }
+            }
+            self.fill_version_columns(package_idx.as_ref());
+        }
+    }
+
+    /// Retrieve the pin history of a package and present it in the history
+    /// panel. Selecting an entry in the panel highlights its version in the tree.
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    ///
+    /// # Returns
+    /// * Ok(()) or an error if the history could not be retrieved
+    pub(crate) fn show_history(&self, package: &str) -> Result<(), BackendError> {
+        let mut panel_title = self.history_panel.title.get()?;
+        let mut table = self.history_table()?;
+        let mut dialog = self.history_panel()?;
+        let history = self.backend.borrow_mut().history(package)?;
+        unsafe {
+            panel_title.set_text(&qs(format!("History of {}", package)));
+            table.clear_contents();
+            table.set_row_count(history.len() as i32);
+            for (row, entry) in history.iter().enumerate() {
+                for (column, text) in entry.columns().iter().enumerate() {
+                    let mut item = QTableWidgetItem::from_q_string(&qs(text));
+                    item.set_tool_tip(&qs(format!(
+                        "changeset {}: {}",
+                        entry.transaction_id, entry.comment
+                    )));
+                    table.set_item(row as i32, column as i32, item.into_ptr());
+                }
+            }
+            table.resize_columns_to_contents();
+            dialog.set_window_title(&qs(format!("{} History", package)));
+            dialog.show();
+            dialog.raise();
+        }
+        *self.history.borrow_mut() = history;
+        *self.history_package.borrow_mut() = Some(package.to_string());
+        Ok(())
+    }
+
+    /// Retrieve the package whose history is presented in the history panel, if any
+    pub(crate) fn history_package(&self) -> Option<String> {
+        self.history_package.borrow().clone()
+    }
+
+    /// Retrieve the entries presented in the history panel
+    pub(crate) fn history(&self) -> Vec<HistoryEntry> {
+        self.history.borrow().clone()
+    }
+
+    /// Retrieve the history panel
+    pub(crate) fn history_panel(&self) -> Result<MutPtr<QDialog>, WidgetError> {
+        self.history_panel.dialog.get()
+    }
+
+    /// Retrieve the table listing the history entries
+    pub(crate) fn history_table(&self) -> Result<MutPtr<QTableWidget>, WidgetError> {
+        self.history_panel.table.get()
+    }
+
+    /// Highlight the version of the history entry at the supplied row of the
+    /// history panel in the tree
+    pub(crate) fn reveal_history_entry(&self, row: i32) {
+        let entry = match self.history.borrow().get(row as usize) {
+            Some(entry) => entry.clone(),
+            None => return,
+        };
+        let package = match self.history_package.borrow().clone() {
+            Some(package) => package,
+            None => return,
+        };
+        if !self.reveal_version(&package, &entry.version) {
+            log::warn!(
+                "{}-{} is not in the tree for the current site",
+                package,
+                entry.version
+            );
+        }
+    }
+
+    /// Expand a package and select one of its versions, scrolling it into view
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    /// * `version` - The version to select
+    ///
+    /// # Returns
+    /// * true if the version was found, false otherwise
+    pub(crate) fn reveal_version(&self, package: &str, version: &str) -> bool {
+        unsafe {
+            let model = live_or_default!(self.model());
+            let mut view = live_or_default!(self.view());
+            // the package's group is loaded, and expanded, to reach the package
+            if self.hierarchy.borrow().is_grouped() {
+                let group = self
+                    .groups
+                    .borrow()
+                    .iter()
+                    .find(|(_, packages)| packages.iter().any(|p| p == package))
+                    .map(|(group, _)| group.clone());
+                if let Some(row) = group.and_then(|group| self.find_top_level_row(&group)) {
+                    let group_idx = model.index_2a(row, 0);
+                    self.load_children(group_idx.as_ref());
+                    view.set_row_hidden(row, &QModelIndex::new(), false);
+                    view.expand(&group_idx);
+                }
+            }
+            let package_idx = match self.find_package(package) {
+                Some(idx) => idx,
+                None => return false,
+            };
+            self.load_children(package_idx.as_ref());
+            let package_item = model.item_from_index(&package_idx);
+            let version_row = match Self::find_child_row(package_item, version) {
+                Some(row) => row,
+                None => return false,
+            };
+            let version_idx = model.index_3a(version_row, 0, &package_idx);
+            view.set_row_hidden(package_idx.row(), &package_idx.parent(), false);
+            view.expand(&package_idx);
+            view.set_current_index(&version_idx);
+            view.scroll_to_1a(&version_idx);
+            true
+        }
+    }
+
+    /// Retrieve whether pins are shown beneath each platform
+    pub(crate) fn show_pins(&self) -> bool {
+        self.show_pins.get()
+    }
+
+    /// Retrieve the locations at which the version at the supplied index is
+    /// pinned at the current site
+    pub(crate) unsafe fn pins_for_version(&self, idx: Ref<QModelIndex>) -> Vec<PinLocation> {
+        let selection = self.selection_from_index(idx);
+        let version = match &selection.version {
+            Some(version) => version,
+            None => return Vec::new(),
+        };
+        match self
+            .backend
+            .borrow_mut()
+            .pins(&selection.package, &selection.site)
+        {
+            Ok(pins) => pins
+                .into_iter()
+                .filter(|pin| &pin.version == version)
+                .collect(),
+            Err(e) => {
+                log::warn!("Unable to retrieve pins for {}: {}", selection.package, e);
+                Vec::new()
+            }
+        }
+    }
+
+    // replace the placeholder beneath a platform with a row per pin, labeled
+    // by role and level. If the version is not pinned for the platform, the
+    // placeholder is replaced by a disabled row saying so.
+    unsafe fn load_pins(&self, idx: Ref<QModelIndex>) {
+        let selection = self.selection_from_index(idx);
+        let platform = selection.platform.clone().unwrap_or_default();
+        let pins = self
+            .pins_for_version(idx)
+            .into_iter()
+            .filter(|pin| pin.platform == platform || pin.platform == "any")
+            .collect::<Vec<_>>();
+        let mut model = live_or_default!(self.model());
+        let mut item = model.item_from_index(idx);
+        model.remove_rows_3a(0, 1, idx);
+        if pins.is_empty() {
+            let mut none = Self::text_item(&format!("not pinned at {}", selection.site));
+            none.set_enabled(false);
+            item.append_row_q_standard_item(none.into_ptr());
+            return;
+        }
+        let icon = QIcon::from_q_string(&qs(self.icons().pin()));
+        for pin in pins {
+            let mut pin_item = Self::text_item(&pin.label());
+            pin_item.set_icon(&icon);
+            pin_item.set_tool_tip(&qs(format!(
+                "role: {}\nlevel: {}\nplatform: {}\nsite: {}",
+                pin.role, pin.level, pin.platform, pin.site
+            )));
+            item.append_row_q_standard_item(pin_item.into_ptr());
+        }
+    }
+
+    /// Expand all of the visible packages to the supplied depth, loading the
+    /// required children in bulk. Versions are fetched with a single query
+    /// for all packages, and platforms with a single query for all versions.
+    /// Rows whose children would take the number of loaded nodes beyond the
+    /// maximum number of eager nodes are not loaded, and are left collapsed.
+    ///
+    /// # Arguments
+    /// * `depth` - The number of levels to expand. 1 reveals versions, 2 reveals platforms.
+    ///
+    /// # Returns
+    /// * The number of nodes loaded
+    pub(crate) fn expand_all(&self, depth: i32) -> usize {
+        if depth < 1 {
+            return 0;
+        }
+        let _loading = self.begin_loading();
+        if !self.hierarchy.borrow().is_default_or_grouped() {
+            return self.expand_levels(depth as usize);
+        }
+        unsafe {
+            let model = live_or_default!(self.model());
+            let root = QModelIndex::new();
+            let mut view = live_or_default!(self.view());
+            let max_nodes = self.max_eager_nodes();
+            let mut loaded = 0;
+
+            // groups are held in memory, so the visible groups are loaded,
+            // and expanded, without counting towards the maximum
+            if self.hierarchy.borrow().is_grouped() {
+                for row in 0..model.row_count_0a() {
+                    if view.is_row_hidden(row, root.as_ref()) {
+                        continue;
+                    }
+                    let group_idx = model.index_2a(row, 0);
+                    self.load_children(group_idx.as_ref());
+                    view.expand(&group_idx);
+                }
+            }
+            let packages = self
+                .package_parents()
+                .iter()
+                .flat_map(|parent| {
+                    (0..model.row_count_1a(parent))
+                        .filter(|row| !view.is_row_hidden(*row, parent))
+                        .map(|row| model.index_3a(row, 0, parent))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let unloaded = packages
+                .iter()
+                .filter(|idx| self.needs_children(idx.as_ref()))
+                .map(|idx| model.item_from_index(idx).text().to_std_string())
+                .collect::<Vec<_>>();
+            let request = LoadRequest::ExpandAll { depth };
+            if !unloaded.is_empty() {
+                let result = self.backend.borrow_mut().versions_for(&unloaded);
+                let mut versions = match result {
+                    Ok(versions) => versions,
+                    Err(err) => {
+                        self.load_failed(request, &err);
+                        return loaded;
+                    }
+                };
+                for idx in &packages {
+                    if !self.needs_children(idx.as_ref()) {
+                        continue;
+                    }
+                    let name = model.item_from_index(idx).text().to_std_string();
+                    if let Some(children) = versions.remove(&name) {
+                        // the packages from the first whose versions would
+                        // exceed the maximum onwards are left unloaded
+                        if loaded + children.len() > max_nodes {
+                            break;
+                        }
+                        loaded += children.len();
+                        self.replace_placeholder(idx.as_ref(), children, true);
+                        self.fill_version_columns(idx.as_ref());
+                        self.annotate_versions(idx.as_ref());
+                    }
+                }
+            }
+
+            let mut platforms: Option<Vec<String>> = None;
+            for idx in &packages {
+                if self.needs_children(idx.as_ref()) {
+                    continue;
+                }
+                view.expand(idx);
+                if depth < 2 {
+                    continue;
+                }
+                for row in 0..model.row_count_1a(idx) {
+                    let version_idx = model.index_3a(row, 0, idx);
+                    if self.needs_children(version_idx.as_ref()) {
+                        if loaded >= max_nodes {
+                            continue;
+                        }
+                        if platforms.is_none() {
+                            let result = self.backend.borrow_mut().platforms();
+                            match result {
+                                Ok(children) => platforms = Some(children),
+                                Err(err) => {
+                                    self.load_failed(request, &err);
+                                    return loaded;
+                                }
+                            }
+                        }
+                        let children = match platforms.as_ref() {
+                            Some(children) => children,
+                            None => continue,
+                        };
+                        if loaded + children.len() > max_nodes {
+                            continue;
+                        }
+                        loaded += children.len();
+                        self.replace_placeholder(
+                            version_idx.as_ref(),
+                            children.clone(),
+                            self.show_pins(),
+                        );
+                        self.decorate_platforms(version_idx.as_ref());
+                    }
+                    if !self.needs_children(version_idx.as_ref()) {
+                        view.expand(&version_idx);
+                    }
+                }
+            }
+            self.load_succeeded(&request);
+            loaded
+        }
+    }
+
+    // expand the visible items level by level, loading their children one
+    // item at a time, for hierarchies which lack a bulk query
+    fn expand_levels(&self, depth: usize) -> usize {
+        unsafe {
+            let mut view = live_or_default!(self.view());
+            let max_nodes = self.max_eager_nodes();
+            let mut loaded = 0;
+            for level in 0..depth {
+                for idx in self.loaded_indexes(level) {
+                    if view.is_row_hidden(idx.row(), &idx.parent()) {
+                        continue;
+                    }
+                    if self.needs_children(idx.as_ref()) {
+                        // items whose children would exceed the maximum are left unloaded
+                        let remaining = max_nodes.saturating_sub(loaded);
+                        if let Some(count) = self.load_children_within(idx.as_ref(), remaining) {
+                            loaded += count;
+                        }
+                    }
+                    if !self.needs_children(idx.as_ref()) {
+                        view.expand(&idx);
+                    }
+                }
+            }
+            loaded
+        }
+    }
+
+    /// Collapse every item in the tree
+    pub(crate) fn collapse_all(&self) {
+        unsafe {
+            let mut view = live_or_default!(self.view());
+            view.collapse_all();
+        }
+    }
+
+    /// Clear the current selection
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn clear_selection(&self) {
+        unsafe {
+            live_or_default!(self.view())
+                .selection_model()
+                .clear_selection();
+        }
+    }
+
+    /// Retrieve the filter combobox
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping the MutPtr of QPushButton, or WidgetError::Destroyed
+    pub(crate) fn filter_cb(&self) -> Result<MutPtr<QPushButton>, WidgetError> {
+        self.filter_cb.get()
+    }
+
+    /// Turn visibility of frame off and on
+    ///
+    /// # Arguments
+    /// * `visible` - boolean indicating the visibility state
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_filter_visibility(&self, visible: bool) {
+        unsafe {
+            let mut filter_frame = live_or_default!(self.filter_frame());
+            filter_frame.set_visible(visible);
+        }
+    }
+
+    /// Set the stylesheet to the internal stylesheet
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// *None
+    pub(crate) fn set_default_stylesheet(&self) {
+        // the built in theme is always available, so only the widgets may be missing
+        if let Err(err) = self.set_theme(Theme::Dark) {
+            log::warn!("unable to apply the default theme: {}", err);
+        }
+    }
+
+    /// Retrieve the file system watcher used to hot reload file based themes
+    pub(crate) fn theme_watcher(&self) -> Result<MutPtr<QFileSystemWatcher>, WidgetError> {
+        self.theme_watcher.get()
+    }
+
+    /// Retrieve the current theme
+    pub(crate) fn theme(&self) -> Theme {
+        self.theme.borrow().clone()
+    }
+
+    /// Retrieve the icon set of the current theme
+    pub(crate) fn icons(&self) -> IconSet {
+        self.theme.borrow().icons()
+    }
+
+    /// Apply a theme, setting its stylesheet and icons. File based themes
+    /// are watched, and reloaded when the file changes.
+    ///
+    /// # Arguments
+    /// * `theme` - The Theme to apply
+    ///
+    /// # Returns
+    /// * Ok(()), or a ThemeError if a file based theme cannot be read or the
+    ///   widgets have been destroyed
+    pub(crate) fn set_theme(&self, theme: Theme) -> Result<(), ThemeError> {
+        let frame = self.widget()?;
+        let stylesheet = theme.stylesheet()?;
+        set_stylesheet_from_str(&stylesheet, frame);
+        *self.theme.borrow_mut() = theme;
+        self.watch_theme();
+        self.apply_icons();
+        Ok(())
+    }
+
+    /// Re-read the stylesheet of a file based theme
+    pub(crate) fn reload_theme(&self) -> Result<(), ThemeError> {
+        self.set_theme(self.theme())
+    }
+
+    // watch the file of the current theme, if it is file based. Editors
+    // frequently replace the file on save, which drops it from the watcher,
+    // so the path is re-added each time.
+    fn watch_theme(&self) {
+        unsafe {
+            let mut watcher = live_or_default!(self.theme_watcher());
+            let files = watcher.files();
+            if !files.is_empty() {
+                watcher.remove_paths(&files);
+            }
+            if let Some(path) = self.theme.borrow().path() {
+                watcher.add_path(&qs(path.to_string_lossy()));
+            }
+        }
+    }
+
+    // set the icons of the header and the extant rows from the current icon set
+    fn apply_icons(&self) {
+        unsafe {
+            let icons = self.icons();
+
+            let mut icon = QIcon::new();
+            icon.add_file_2a(&qs(icons.site()), QSize::new_2a(12, 12).as_ref());
+            let mut site_label = live_or_default!(self.site_label.get());
+            site_label.set_pixmap(&icon.pixmap_int(12));
+
+            let mut icon = QIcon::new();
+            icon.add_file_2a(&qs(icons.filter_off()), QSize::new_2a(10, 10).as_ref());
+            icon.add_file_4a(
+                &qs(icons.filter_on()),
+                QSize::new_2a(10, 10).as_ref(),
+                Mode::Normal,
+                State::On,
+            );
+            let mut filter_cb = live_or_default!(self.filter_cb());
+            filter_cb.set_icon(&icon);
+
+            let package_icon = self.level_icon(self.root_level());
+            let pin_icon = QIcon::from_q_string(&qs(icons.pin()));
+            let model = live_or_default!(self.model());
+            for row in 0..model.row_count_0a() {
+                let mut package_item = model.item_2a(row, 0);
+                package_item.set_icon(&package_icon);
+                for version_row in 0..package_item.row_count() {
+                    let mut icon_item = package_item.child_2a(version_row, self.pin_column());
+                    if !icon_item.is_null() && !icon_item.icon().is_null() {
+                        icon_item.set_icon(&pin_icon);
+                    }
+                }
+            }
+        }
+        // the highlighted matches display the package icon
+        self.apply_filter();
+    }
+
+    /// Change the max number of items displayed in the combobox's dropdown
+    /// list
+    ///
+    /// # Arguments
+    /// * `max` - Maximum number of visible items in the comobobox's dropdown
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_cb_max_visible_items(&self, max: i32) {
+        unsafe {
+            live_or_default!(self.combobox()).set_max_visible_items(max);
+        }
+    }
+
+    // Create a checkable action per optional column, which is displayed in the
+    // header's context menu in order to toggle the column's visibility
+    unsafe fn create_column_actions(view: MutPtr<QTreeView>) -> HashMap<Column, Guarded<QAction>> {
+        let mut header = view.header();
+        header.set_context_menu_policy(ContextMenuPolicy::ActionsContextMenu);
+        let mut actions = HashMap::new();
+        for column in Column::ALL.iter() {
+            let mut action = QAction::from_q_string_q_object(&qs(column.title()), header);
+            action.set_checkable(true);
+            action.set_checked(true);
+            let action_ptr = action.into_ptr();
+            header.add_action(action_ptr);
+            actions.insert(*column, Guarded::new(action_ptr));
+        }
+        actions
+    }
+
+    // Create a QShortcut per ShortcutAction, parented to the widget which
+    // should have focus for the shortcut to be active.
+    unsafe fn create_shortcuts(
+        main: MutPtr<QFrame>,
+        filter_frame: MutPtr<QFrame>,
+        filter: MutPtr<QLineEdit>,
+        view: MutPtr<QTreeView>,
+    ) -> HashMap<ShortcutAction, Guarded<QShortcut>> {
+        let mut shortcuts = HashMap::new();
+        for action in ShortcutAction::ALL.iter() {
+            let (parent, context): (MutPtr<QWidget>, ShortcutContext) = match action {
+                ShortcutAction::OpenFilter => (
+                    main.static_upcast_mut(),
+                    ShortcutContext::WidgetWithChildrenShortcut,
+                ),
+                ShortcutAction::CloseFilter => (
+                    filter_frame.static_upcast_mut(),
+                    ShortcutContext::WidgetWithChildrenShortcut,
+                ),
+                ShortcutAction::JumpToMatch => {
+                    (filter.static_upcast_mut(), ShortcutContext::WidgetShortcut)
+                }
+                ShortcutAction::ExpandPackage => {
+                    (view.static_upcast_mut(), ShortcutContext::WidgetShortcut)
+                }
+                ShortcutAction::Undo | ShortcutAction::Redo => (
+                    main.static_upcast_mut(),
+                    ShortcutContext::WidgetWithChildrenShortcut,
+                ),
+            };
+            let mut shortcut = QShortcut::new_2a(
+                &QKeySequence::from_q_string(&qs(action.default_key_sequence())),
+                parent,
+            );
+            shortcut.set_context(context);
+            shortcuts.insert(*action, Guarded::new(shortcut.into_ptr()));
+        }
+        shortcuts
+    }
+
+    // Create a new `CppBox`'ed QFrame instance
+    unsafe fn new_qframe() -> CppBox<QFrame> {
+        let mut qf = QFrame::new_0a();
+        qf.set_object_name(&qs("PackageFilterFrame"));
+        let layout = create_hlayout();
+        qf.set_layout(layout.into_ptr());
+        qf
+    }
+
+    unsafe fn new_filter(parent: MutPtr<QFrame>) -> MutPtr<QLineEdit> {
+        let label = QLabel::from_q_string(&qs("Package Filter"));
+        parent.layout().add_widget(label.into_ptr());
+        let mut qle = QLineEdit::new();
+        qle.set_attribute_2a(WidgetAttribute::WAMacShowFocusRect, false);
+        qle.set_object_name(&qs("PackageFilter"));
+        let qle_ptr = qle.as_mut_ptr();
+        parent.layout().add_widget(qle.into_ptr());
+        qle_ptr
+    }
+
+    // the label displaying the number of packages matching the filter
+    unsafe fn new_match_count(parent: MutPtr<QFrame>) -> MutPtr<QLabel> {
+        let mut label = QLabel::new();
+        label.set_object_name(&qs("FilterMatchCount"));
+        let label_ptr = label.as_mut_ptr();
+        parent.layout().add_widget(label.into_ptr());
+        label_ptr
+    }
+
+    fn create_cbox<I>(layout: I) -> Header
+    where
+        I: CastInto<MutPtr<QLayout>>,
+    {
+        unsafe {
+            // combo_box
+            let mut horiz_frame = QFrame::new_0a();
+            horiz_frame.set_object_name(&qs("SitesCBFrame"));
+            let mut h_layout = create_hlayout();
+            let mut h_layout_p = h_layout.as_mut_ptr();
+            horiz_frame.set_layout(h_layout.into_ptr());
+
+            // the busy indicator is shown during site switches and bulk loads
+            let mut busy_l = QLabel::new();
+            let busy_l_ptr = busy_l.as_mut_ptr();
+            busy_l.set_object_name(&qs("BusyIndicator"));
+            busy_l.set_visible(false);
+            h_layout_p.add_widget(busy_l.into_ptr());
+
+            // the site label's pixmap is set along with the rest of the
+            // theme's icons
+            let mut site_l = QLabel::from_q_string(&qs("Site"));
+            let site_l_ptr = site_l.as_mut_ptr();
+            site_l.set_object_name(&qs("SiteLabel"));
+            h_layout_p.add_stretch_1a(1);
 
             h_layout_p.add_widget(site_l.into_ptr());
 
@@ -408,22 +3961,133 @@ impl InnerTreeView {
             filter_btn.set_object_name(&qs("packageFilterCheckbox"));
             filter_btn.set_checkable(true);
             filter_btn.set_tool_tip(&qs("Display the Package filter control"));
-            let mut icon = QIcon::new();
-            icon.add_file_2a(
-                &qs(":/images/filter_white_sm.svg"),
-                QSize::new_2a(10, 10).as_ref(),
-            );
-            icon.add_file_4a(
-                &qs(":/images/filter_blue_sm.svg"),
-                QSize::new_2a(10, 10).as_ref(),
-                Mode::Normal,
-                State::On,
-            );
-            filter_btn.set_icon(&icon);
             h_layout_p.add_widget(filter_btn.into_ptr());
+
+            let mut expand_all_btn = QPushButton::from_q_string(&qs("+"));
+            let expand_all_btn_ptr = expand_all_btn.as_mut_ptr();
+            expand_all_btn.set_object_name(&qs("expandAllButton"));
+            expand_all_btn.set_tool_tip(&qs("Expand all of the visible packages"));
+            h_layout_p.add_widget(expand_all_btn.into_ptr());
+
+            let mut collapse_all_btn = QPushButton::from_q_string(&qs("-"));
+            let collapse_all_btn_ptr = collapse_all_btn.as_mut_ptr();
+            collapse_all_btn.set_object_name(&qs("collapseAllButton"));
+            collapse_all_btn.set_tool_tip(&qs("Collapse all of the packages"));
+            h_layout_p.add_widget(collapse_all_btn.into_ptr());
+
             layout.cast_into().add_widget(horiz_frame.into_ptr());
 
-            (cbox_p, filter_btn_ptr)
+            Header {
+                busy_label: busy_l_ptr,
+                site_label: site_l_ptr,
+                cbox: cbox_p,
+                filter_btn: filter_btn_ptr,
+                expand_all_btn: expand_all_btn_ptr,
+                collapse_all_btn: collapse_all_btn_ptr,
+            }
+        }
+    }
+
+    // stack the view with the overlay displayed in its place when it is
+    // empty, or a load has failed
+    unsafe fn create_overlay(treeview: CppBox<QTreeView>) -> Overlay {
+        let mut stack = QStackedWidget::new_0a();
+        stack.set_object_name(&qs("TreeStack"));
+        stack.add_widget(treeview.into_ptr());
+
+        let mut frame = QFrame::new_0a();
+        frame.set_object_name(&qs("TreeOverlay"));
+        let mut layout = create_vlayout();
+        let mut layout_ptr = layout.as_mut_ptr();
+        frame.set_layout(layout.into_ptr());
+
+        let mut label = QLabel::new();
+        label.set_object_name(&qs("OverlayLabel"));
+        label.set_alignment(AlignmentFlag::AlignCenter.into());
+        label.set_word_wrap(true);
+        let label_ptr = label.as_mut_ptr();
+
+        let mut retry_btn = QPushButton::from_q_string(&qs(&OverlayText::default().retry));
+        retry_btn.set_object_name(&qs("OverlayRetryButton"));
+        retry_btn.set_visible(false);
+        let retry_btn_ptr = retry_btn.as_mut_ptr();
+
+        layout_ptr.add_stretch_0a();
+        layout_ptr.add_widget(label.into_ptr());
+        layout_ptr.add_widget_3a(retry_btn.into_ptr(), 0, AlignmentFlag::AlignHCenter.into());
+        layout_ptr.add_stretch_0a();
+        stack.add_widget(frame.into_ptr());
+
+        Overlay {
+            stack: Guarded::new(stack.into_ptr()),
+            label: Guarded::new(label_ptr),
+            retry_btn: Guarded::new(retry_btn_ptr),
+        }
+    }
+
+    // create the table listing the differences between two sites side by
+    // side, in a splitter beside the supplied stack. The table is hidden
+    // until sites are compared.
+    unsafe fn create_comparison_table(
+        stack: MutPtr<QStackedWidget>,
+    ) -> (MutPtr<QSplitter>, MutPtr<QTableWidget>) {
+        let mut splitter = QSplitter::new_0a();
+        splitter.set_object_name(&qs("ComparisonSplitter"));
+        splitter.add_widget(stack);
+
+        let mut table = QTableWidget::new_0a();
+        let table_ptr = table.as_mut_ptr();
+        table.set_object_name(&qs("ComparisonTable"));
+        table.set_column_count(COMPARISON_COLUMNS.len() as i32 + 2);
+        table.set_edit_triggers(EditTrigger::NoEditTriggers.into());
+        table.set_selection_behavior(SelectionBehavior::SelectRows);
+        table.set_selection_mode(q_abstract_item_view::SelectionMode::SingleSelection);
+        table.horizontal_header().set_stretch_last_section(true);
+        table.vertical_header().set_visible(false);
+        table.set_visible(false);
+        splitter.add_widget(table.into_ptr());
+
+        (splitter.into_ptr(), table_ptr)
+    }
+
+    // create the panel presenting the pin history of a package. It is a tool
+    // window owned by the tree's frame, hidden until a history is requested.
+    fn create_history_panel(parent: MutPtr<QFrame>) -> HistoryPanel {
+        unsafe {
+            let mut dialog = QDialog::new_1a(parent);
+            dialog.set_object_name(&qs("HistoryPanel"));
+            dialog.set_modal(false);
+            dialog.resize_2a(800, 400);
+            let mut layout = create_vlayout();
+            let mut layout_ptr = layout.as_mut_ptr();
+            dialog.set_layout(layout.into_ptr());
+
+            let mut title = QLabel::new();
+            let title_ptr = title.as_mut_ptr();
+            title.set_object_name(&qs("HistoryTitle"));
+            layout_ptr.add_widget(title.into_ptr());
+
+            let mut table = QTableWidget::new_0a();
+            let table_ptr = table.as_mut_ptr();
+            table.set_object_name(&qs("HistoryTable"));
+            table.set_column_count(HISTORY_COLUMNS.len() as i32);
+            let mut labels = QStringList::new();
+            for label in HISTORY_COLUMNS.iter() {
+                labels.append_q_string(&qs(*label));
+            }
+            table.set_horizontal_header_labels(&labels);
+            table.set_edit_triggers(EditTrigger::NoEditTriggers.into());
+            table.set_selection_behavior(SelectionBehavior::SelectRows);
+            table.set_selection_mode(q_abstract_item_view::SelectionMode::SingleSelection);
+            table.horizontal_header().set_stretch_last_section(true);
+            table.vertical_header().set_visible(false);
+            layout_ptr.add_widget(table.into_ptr());
+
+            HistoryPanel {
+                dialog: Guarded::new(dialog.into_ptr()),
+                title: Guarded::new(title_ptr),
+                table: Guarded::new(table_ptr),
+            }
         }
     }
 }
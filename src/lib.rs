@@ -5,19 +5,71 @@
 //! # Example Usage
 //! ```ignore
 //! ...
-//! // create the widget, passing it a pointer to its intended parent
-//! let mut mytree = tree::DistributionTreeView::create(main_widget_ptr);
+//! // The icons used by the tree are embedded in the crate, and registered
+//! // automatically when the tree is created. Call init_resources
+//! // directly to make them available before then.
+//! pbgui_tree::init_resources();
+//!
+//! // create the widget, passing it a pointer to its intended parent, which
+//! // must have a layout
+//! let mut mytree = tree::DistributionTreeView::create(main_widget_ptr)?;
+//!
+//! // or configure it up front with the builder
+//! let mytree = builder::DistributionTreeViewBuilder::new()
+//!     .site("portland")
+//!     .theme(theme::Theme::Light)
+//!     .build(main_widget_ptr)?;
+//!
+//! // or create it without a parent, and place its widget wherever you like
+//! let mytree = tree::DistributionTreeView::new();
+//! splitter.add_widget(mytree.widget()?);
 //!
 //! // set the default stylesheet
 //! mytree.set_default_stylesheet();
 //!
+//! // or switch to the built in light theme
+//! mytree.set_theme(theme::Theme::Light)?;
+//!
 //! // set the packages to be displayed under the root in the treeview
 //! mytree.set_packages(package_list);
 //!
 //! // Set the sites to be displayed in the sites pulldown, along with the initial
 //! // site.
 //! mytree.set_sites(site_list, "portland");
+//!
+//! // optionally allow the user to select multiple rows, and respond to
+//! // changes in the selection
+//! mytree.set_selection_mode(selection::SelectionMode::Extended);
+//! mytree.selection_changed().connect(|selections| println!("{:?}", selections));
+//!
+//! // optionally bucket the packages into collapsible groups by prefix
+//! mytree.set_grouping(Some(grouping::Grouping::Prefixes(vec!["houdini".into(), "maya".into()])))?;
 //! ```
 pub(crate) mod api;
+pub(crate) mod backend;
+pub mod builder;
+pub mod cache;
+pub mod callback;
+pub mod columns;
+pub mod compare;
+pub(crate) mod delegate;
+pub mod edit;
+pub mod filter;
+pub mod grouping;
+pub mod hierarchy;
+pub mod history;
 pub(crate) mod inner_tree;
+pub mod live;
+pub mod loading;
+pub mod mime;
+pub mod overlay;
+pub mod pins;
+pub mod platform;
+pub mod resources;
+pub mod selection;
+pub mod shortcuts;
+pub mod theme;
 pub mod tree;
+pub mod undo;
+pub mod widget;
+pub use resources::init_resources;
@@ -0,0 +1,176 @@
+//! Live updates via Postgres LISTEN/NOTIFY.
+//!
+//! When live updates are started, a background thread opens a dedicated
+//! connection to the packrat database and `LISTEN`s on a channel. The payload
+//! of each notification describes a single change, in one of the following
+//! formats:
+//!
+//! * `package:<package>` - a package was added
+//! * `distribution:<package>:<version>` - a distribution was added
+//! * `versionpin:<site>:<package>:<version>` - a version was pinned at a site
+//!
+//! Notifications are typically sent by triggers in the database. For example:
+//! ```sql
+//! SELECT pg_notify('packrat_updates', 'distribution:maya:2020.1');
+//! ```
+use crate::api::ClientProxy;
+use crate::backend::BackendError;
+use fallible_iterator::FallibleIterator;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// The default channel LISTENed on
+pub const DEFAULT_CHANNEL: &'static str = "packrat_updates";
+
+// how long the listener thread blocks waiting on a notification before
+// checking whether it has been asked to stop
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A change in the database, as described by a notification payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiveUpdate {
+    Package(String),
+    Distribution {
+        package: String,
+        version: String,
+    },
+    VersionPin {
+        site: String,
+        package: String,
+        version: String,
+    },
+}
+
+impl LiveUpdate {
+    /// Parse a notification payload into a LiveUpdate
+    ///
+    /// # Arguments
+    /// * `payload` - The payload of the notification
+    ///
+    /// # Returns
+    /// * Some LiveUpdate, or None if the payload is not understood
+    pub fn parse(payload: &str) -> Option<LiveUpdate> {
+        let pieces = payload.trim().split(':').collect::<Vec<_>>();
+        match pieces.as_slice() {
+            ["package", package] => Some(LiveUpdate::Package(package.to_string())),
+            ["distribution", package, version] => Some(LiveUpdate::Distribution {
+                package: package.to_string(),
+                version: version.to_string(),
+            }),
+            ["versionpin", site, package, version] => Some(LiveUpdate::VersionPin {
+                site: site.to_string(),
+                package: package.to_string(),
+                version: version.to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Owns the background thread which LISTENs for notifications. The thread
+/// is signalled to stop when the Listener is dropped, and exits once its
+/// current poll returns.
+pub(crate) struct Listener {
+    receiver: Receiver<LiveUpdate>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Listener {
+    /// Connect to the database and start LISTENing on the supplied channel
+    ///
+    /// # Arguments
+    /// * `channel_name` - The name of the channel to LISTEN on
+    ///
+    /// # Returns
+    /// * Result wrapping the Listener, or an error if the connection fails
+    pub(crate) fn spawn(channel_name: &str) -> Result<Self, BackendError> {
+        let mut client = ClientProxy::connect()?;
+        // quote the channel so that it is treated as an identifier
+        client.batch_execute(&format!("LISTEN \"{}\"", channel_name.replace('"', "")))?;
+
+        let (sender, receiver) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                let mut notifications = client.notifications();
+                match notifications.timeout_iter(POLL_INTERVAL).next() {
+                    Ok(Some(notification)) => match LiveUpdate::parse(notification.payload()) {
+                        Some(update) => {
+                            if sender.send(update).is_err() {
+                                break;
+                            }
+                        }
+                        None => log::warn!(
+                            "Ignoring unknown notification payload: {}",
+                            notification.payload()
+                        ),
+                    },
+                    Ok(None) => (),
+                    Err(e) => {
+                        log::error!("Live updates stopped: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Self { receiver, stop })
+    }
+
+    /// Retrieve the updates received since the last call, without blocking
+    pub(crate) fn pending(&self) -> Vec<LiveUpdate> {
+        let mut updates = Vec::new();
+        while let Ok(update) = self.receiver.try_recv() {
+            updates.push(update);
+        }
+        updates
+    }
+}
+
+impl Drop for Listener {
+    // the thread is signalled rather than joined, so that dropping the
+    // Listener never blocks the gui thread for up to a poll interval. The
+    // detached thread exits, closing its connection, once its poll returns.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_understands_each_payload() {
+        assert_eq!(
+            LiveUpdate::parse("package:maya"),
+            Some(LiveUpdate::Package("maya".to_string()))
+        );
+        assert_eq!(
+            LiveUpdate::parse(" distribution:maya:2020.1\n"),
+            Some(LiveUpdate::Distribution {
+                package: "maya".to_string(),
+                version: "2020.1".to_string(),
+            })
+        );
+        assert_eq!(
+            LiveUpdate::parse("versionpin:portland:maya:2020.1"),
+            Some(LiveUpdate::VersionPin {
+                site: "portland".to_string(),
+                package: "maya".to_string(),
+                version: "2020.1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_rejects_unknown_payloads() {
+        assert_eq!(LiveUpdate::parse(""), None);
+        assert_eq!(LiveUpdate::parse("package"), None);
+        assert_eq!(LiveUpdate::parse("distribution:maya"), None);
+        assert_eq!(LiveUpdate::parse("role:lighting"), None);
+    }
+}
@@ -0,0 +1,49 @@
+/// The default number of distributions above which eager loading falls back
+/// to lazy loading.
+pub const DEFAULT_MAX_EAGER_DISTRIBUTIONS: usize = 50_000;
+
+/// The text displayed beside the animated indicator while loading
+pub const LOADING_TEXT: &str = "Loading…";
+
+// the frames of the animated indicator displayed while loading
+pub(crate) const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+// the interval, in milliseconds, at which the indicator advances
+pub(crate) const SPINNER_INTERVAL: i32 = 80;
+
+/// Renders the indicator at the supplied frame, along with the loading text
+pub(crate) fn spinner_text(frame: usize) -> String {
+    format!(
+        "{} {}",
+        SPINNER_FRAMES[frame % SPINNER_FRAMES.len()],
+        LOADING_TEXT
+    )
+}
+
+/// Determines how the tree fetches the versions of its packages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadingMode {
+    /// Query the versions of a package when it is expanded (the default)
+    Lazy,
+    /// Fetch all of the distributions in a single query, and serve expansions
+    /// from an in memory index. Distributions are shared by every site, so the
+    /// index survives a change of site. If there are more than
+    /// `max_distributions` distributions, fall back to lazy loading without
+    /// fetching them.
+    Eager { max_distributions: usize },
+}
+
+impl LoadingMode {
+    /// New up an eager LoadingMode using the default maximum number of distributions
+    pub fn eager() -> Self {
+        LoadingMode::Eager {
+            max_distributions: DEFAULT_MAX_EAGER_DISTRIBUTIONS,
+        }
+    }
+}
+
+impl Default for LoadingMode {
+    fn default() -> Self {
+        LoadingMode::Lazy
+    }
+}
@@ -0,0 +1,174 @@
+//! Encode and decode distribution selections as mime data, so that rows may be
+//! dragged out of the tree and dropped on other widgets.
+//!
+//! The selections are carried under the `DISTRIBUTION_MIME_TYPE` mime type,
+//! one selection per line, with the package, version, platform and site
+//! separated by tabs. A `text/plain` fallback carrying the distribution
+//! names (eg `maya-2020.1`) is provided for drop targets which know nothing
+//! about the custom format.
+//!
+//! # Example Usage
+//! ```ignore
+//! // in the drop target's dropEvent handler
+//! if let Some(selections) = mime::decode_mime_data(event.mime_data()) {
+//!     for selection in selections {
+//!         println!("{:?}", selection);
+//!     }
+//! }
+//! ```
+use crate::selection::DistributionSelection;
+use qt_core::{QByteArray, QMimeData, QString};
+use qt_widgets::cpp_core::{CppBox, Ref};
+use rustqt_utils::qs;
+
+/// The mime type used to carry serialized DistributionSelections
+pub const DISTRIBUTION_MIME_TYPE: &'static str = "application/x-pbgui-distribution";
+
+const FIELD_SEP: char = '\t';
+const RECORD_SEP: char = '\n';
+
+/// Serialize a slice of DistributionSelections into a String
+///
+/// # Arguments
+/// * `selections` - The selections to serialize
+///
+/// # Returns
+/// * String
+pub fn encode_selections(selections: &[DistributionSelection]) -> String {
+    selections
+        .iter()
+        .map(|selection| {
+            [
+                selection.package.as_str(),
+                selection.version.as_ref().map(|v| v.as_str()).unwrap_or(""),
+                selection
+                    .platform
+                    .as_ref()
+                    .map(|p| p.as_str())
+                    .unwrap_or(""),
+                selection.site.as_str(),
+            ]
+            .join(&FIELD_SEP.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(&RECORD_SEP.to_string())
+}
+
+/// Deserialize a str, produced by `encode_selections`, into a vector of
+/// DistributionSelections. Malformed records are skipped.
+///
+/// # Arguments
+/// * `input` - The serialized selections
+///
+/// # Returns
+/// * Vector of DistributionSelection
+pub fn decode_selections(input: &str) -> Vec<DistributionSelection> {
+    fn non_empty(field: &str) -> Option<String> {
+        if field == "" {
+            None
+        } else {
+            Some(field.to_string())
+        }
+    }
+    input
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let fields = record.split(FIELD_SEP).collect::<Vec<_>>();
+            if fields.len() != 4 || fields[0] == "" {
+                return None;
+            }
+            Some(DistributionSelection::new(
+                fields[0],
+                non_empty(fields[1]),
+                non_empty(fields[2]),
+                fields[3],
+            ))
+        })
+        .collect()
+}
+
+/// Build a QMimeData carrying the supplied selections under
+/// `DISTRIBUTION_MIME_TYPE`, along with a `text/plain` fallback.
+///
+/// # Arguments
+/// * `selections` - The selections to carry
+///
+/// # Returns
+/// * CppBox wrapped QMimeData
+pub fn to_mime_data(selections: &[DistributionSelection]) -> CppBox<QMimeData> {
+    unsafe {
+        let mut mime = QMimeData::new();
+        let encoded = encode_selections(selections);
+        mime.set_data(
+            &qs(DISTRIBUTION_MIME_TYPE),
+            &QByteArray::from_slice(encoded.as_bytes()),
+        );
+        let text = selections
+            .iter()
+            .map(|selection| {
+                selection
+                    .distribution()
+                    .unwrap_or_else(|| selection.package.clone())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        mime.set_text(&qs(text));
+        mime
+    }
+}
+
+/// Decode the selections carried by a QMimeData, if it has the
+/// `DISTRIBUTION_MIME_TYPE` format.
+///
+/// # Arguments
+/// * `mime` - A reference to the QMimeData received by the drop target
+///
+/// # Returns
+/// * Some Vector of DistributionSelection, or None if the mime data does
+/// not carry distributions
+pub fn decode_mime_data(mime: Ref<QMimeData>) -> Option<Vec<DistributionSelection>> {
+    unsafe {
+        if !mime.has_format(&qs(DISTRIBUTION_MIME_TYPE)) {
+            return None;
+        }
+        let data = mime.data(&qs(DISTRIBUTION_MIME_TYPE));
+        let decoded = QString::from_utf8_q_byte_array(&data).to_std_string();
+        Some(decode_selections(&decoded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selections_survive_a_round_trip() {
+        let selections = vec![
+            DistributionSelection::new("maya", None, None, "portland"),
+            DistributionSelection::new("maya", Some("2020.1".to_string()), None, "portland"),
+            DistributionSelection::new(
+                "nuke",
+                Some("12.1".to_string()),
+                Some("cent7_64".to_string()),
+                "playa",
+            ),
+        ];
+        let encoded = encode_selections(&selections);
+        assert_eq!(decode_selections(&encoded), selections);
+    }
+
+    #[test]
+    fn malformed_records_are_skipped() {
+        let decoded = decode_selections("maya\t2020.1\t\tportland\nnuke\t12.1\n\t1.0\t\tplaya\n");
+        assert_eq!(
+            decoded,
+            vec![DistributionSelection::new(
+                "maya",
+                Some("2020.1".to_string()),
+                None,
+                "portland"
+            )]
+        );
+        assert!(decode_selections("").is_empty());
+    }
+}
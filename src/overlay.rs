@@ -0,0 +1,100 @@
+/// A load from the database which may fail, and be retried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadRequest {
+    /// Loading the top level items, via `load_packages`
+    Packages,
+    /// Loading the sites, via `load_sites`. `None` selects the first site.
+    Sites { current: Option<String> },
+    /// Expanding the tree to the supplied depth, via `expand_all`
+    ExpandAll { depth: i32 },
+}
+
+/// A load which failed, along with the error it failed with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadFailure {
+    pub request: LoadRequest,
+    pub error: String,
+}
+
+/// The states displayed in place of the rows of the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlayState {
+    /// There are no packages
+    Empty,
+    /// None of the packages match the filter
+    NoMatches,
+    /// A load failed. The overlay offers to retry it.
+    Error,
+}
+
+/// The text of the overlays. Within the text, `{site}` is replaced by the
+/// current site, `{filter}` by the filter text, and `{error}` by the error
+/// of the failed load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlayText {
+    pub empty: String,
+    pub no_matches: String,
+    pub error: String,
+    /// The text of the button which retries the failed load
+    pub retry: String,
+}
+
+impl Default for OverlayText {
+    fn default() -> Self {
+        Self {
+            empty: "No packages for site {site}".to_string(),
+            no_matches: "No matches for filter '{filter}'".to_string(),
+            error: "Cannot reach packrat database".to_string(),
+            retry: "Retry".to_string(),
+        }
+    }
+}
+
+impl OverlayText {
+    /// Render the text of an overlay
+    ///
+    /// # Arguments
+    /// * `state` - The OverlayState
+    /// * `site` - The current site
+    /// * `filter` - The filter text
+    /// * `error` - The error of the failed load, if any
+    ///
+    /// # Returns
+    /// * The text, with its placeholders replaced
+    pub fn render(&self, state: OverlayState, site: &str, filter: &str, error: &str) -> String {
+        let text = match state {
+            OverlayState::Empty => &self.empty,
+            OverlayState::NoMatches => &self.no_matches,
+            OverlayState::Error => &self.error,
+        };
+        text.replace("{site}", site)
+            .replace("{filter}", filter)
+            .replace("{error}", error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_replaces_the_placeholders() {
+        let text = OverlayText::default();
+        assert_eq!(
+            text.render(OverlayState::Empty, "portland", "", ""),
+            "No packages for site portland"
+        );
+        assert_eq!(
+            text.render(OverlayState::NoMatches, "portland", "maya", ""),
+            "No matches for filter 'maya'"
+        );
+        let text = OverlayText {
+            error: "{site}: {error}".to_string(),
+            ..OverlayText::default()
+        };
+        assert_eq!(
+            text.render(OverlayState::Error, "playa", "", "timed out"),
+            "playa: timed out"
+        );
+    }
+}
@@ -0,0 +1,16 @@
+/// Where a version is pinned: the role, level and platform, at a site
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PinLocation {
+    pub version: String,
+    pub platform: String,
+    pub role: String,
+    pub level: String,
+    pub site: String,
+}
+
+impl PinLocation {
+    /// Retrieve the label displayed in the tree for the pin (eg `lighting @ dev01.rd`)
+    pub fn label(&self) -> String {
+        format!("{} @ {}", self.role, self.level)
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+/// The operating system family of a platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OsFamily {
+    Linux,
+    Windows,
+    MacOs,
+    Unknown,
+}
+
+impl OsFamily {
+    /// Guess the OsFamily from a platform name such as `cent7_64` or `win10_64`
+    ///
+    /// # Arguments
+    /// * `name` - The name of the platform
+    ///
+    /// # Returns
+    /// * OsFamily
+    pub fn guess(name: &str) -> Self {
+        let name = name.to_lowercase();
+        let starts_with_any = |prefixes: &[&str]| prefixes.iter().any(|p| name.starts_with(p));
+        if starts_with_any(&[
+            "cent", "rhel", "rocky", "alma", "linux", "ubuntu", "fedora", "debian",
+        ]) {
+            OsFamily::Linux
+        } else if starts_with_any(&["win"]) {
+            OsFamily::Windows
+        } else if starts_with_any(&["osx", "mac", "darwin"]) {
+            OsFamily::MacOs
+        } else {
+            OsFamily::Unknown
+        }
+    }
+
+    /// Retrieve the resource path of the icon bundled for the OsFamily
+    pub fn icon(&self) -> Option<&'static str> {
+        match self {
+            OsFamily::Linux => Some(":/images/os_linux.svg"),
+            OsFamily::Windows => Some(":/images/os_windows.svg"),
+            OsFamily::MacOs => Some(":/images/os_macos.svg"),
+            OsFamily::Unknown => None,
+        }
+    }
+}
+
+/// Describes how a platform is presented in the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformDescriptor {
+    /// The human friendly label displayed in place of the platform name
+    pub label: String,
+    /// The operating system family of the platform
+    pub os: OsFamily,
+    /// The icon displayed next to the platform. If None, the OsFamily's icon is used.
+    pub icon: Option<String>,
+}
+
+impl PlatformDescriptor {
+    /// New up a PlatformDescriptor using the OsFamily's bundled icon
+    ///
+    /// # Arguments
+    /// * `label` - The human friendly label
+    /// * `os` - The OsFamily of the platform
+    ///
+    /// # Returns
+    /// * PlatformDescriptor instance
+    pub fn new<L: Into<String>>(label: L, os: OsFamily) -> Self {
+        Self {
+            label: label.into(),
+            os,
+            icon: None,
+        }
+    }
+
+    /// Replace the icon with one supplied by the host (eg a qt resource path)
+    pub fn with_icon<I: Into<String>>(mut self, icon: I) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Retrieve the path of the icon to display, if any
+    pub fn icon_path(&self) -> Option<&str> {
+        match &self.icon {
+            Some(icon) => Some(icon.as_str()),
+            None => self.os.icon(),
+        }
+    }
+}
+
+/// Maps platform names to PlatformDescriptors. Platforms which have not
+/// been registered are described by guessing their OsFamily from their
+/// name and using the name as the label.
+#[derive(Debug, Clone)]
+pub struct PlatformRegistry {
+    descriptors: HashMap<String, PlatformDescriptor>,
+}
+
+impl Default for PlatformRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(
+            "cent6_64",
+            PlatformDescriptor::new("CentOS 6 (64-bit)", OsFamily::Linux),
+        );
+        registry.register(
+            "cent7_64",
+            PlatformDescriptor::new("CentOS 7 (64-bit)", OsFamily::Linux),
+        );
+        registry.register(
+            "cent8_64",
+            PlatformDescriptor::new("CentOS 8 (64-bit)", OsFamily::Linux),
+        );
+        registry.register(
+            "win7_64",
+            PlatformDescriptor::new("Windows 7 (64-bit)", OsFamily::Windows),
+        );
+        registry.register(
+            "win10_64",
+            PlatformDescriptor::new("Windows 10 (64-bit)", OsFamily::Windows),
+        );
+        registry.register(
+            "osx_64",
+            PlatformDescriptor::new("macOS (64-bit)", OsFamily::MacOs),
+        );
+        registry
+    }
+}
+
+impl PlatformRegistry {
+    /// New up a PlatformRegistry without any registered platforms
+    pub fn empty() -> Self {
+        Self {
+            descriptors: HashMap::new(),
+        }
+    }
+
+    /// Register a descriptor for a platform, replacing any extant descriptor
+    ///
+    /// # Arguments
+    /// * `name` - The name of the platform as stored in the database (eg `cent7_64`)
+    /// * `descriptor` - The PlatformDescriptor
+    ///
+    /// # Returns
+    /// * None
+    pub fn register<N: Into<String>>(&mut self, name: N, descriptor: PlatformDescriptor) {
+        self.descriptors.insert(name.into(), descriptor);
+    }
+
+    /// Remove the descriptor registered for a platform
+    pub fn unregister(&mut self, name: &str) {
+        self.descriptors.remove(name);
+    }
+
+    /// Retrieve the descriptor of a platform, falling back on a guess if
+    /// the platform has not been registered
+    ///
+    /// # Arguments
+    /// * `name` - The name of the platform
+    ///
+    /// # Returns
+    /// * PlatformDescriptor
+    pub fn describe(&self, name: &str) -> PlatformDescriptor {
+        match self.descriptors.get(name) {
+            Some(descriptor) => descriptor.clone(),
+            None => PlatformDescriptor::new(name, OsFamily::guess(name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guess_recognizes_common_platform_names() {
+        assert_eq!(OsFamily::guess("cent7_64"), OsFamily::Linux);
+        assert_eq!(OsFamily::guess("Rocky9"), OsFamily::Linux);
+        assert_eq!(OsFamily::guess("WIN10_64"), OsFamily::Windows);
+        assert_eq!(OsFamily::guess("osx_64"), OsFamily::MacOs);
+        assert_eq!(OsFamily::guess("darwin"), OsFamily::MacOs);
+        assert_eq!(OsFamily::guess("solaris"), OsFamily::Unknown);
+        assert_eq!(OsFamily::Unknown.icon(), None);
+    }
+
+    #[test]
+    fn unregistered_platforms_are_guessed() {
+        let mut registry = PlatformRegistry::default();
+        assert_eq!(registry.describe("cent7_64").label, "CentOS 7 (64-bit)");
+        let descriptor = registry.describe("ubuntu20");
+        assert_eq!(descriptor.label, "ubuntu20");
+        assert_eq!(descriptor.os, OsFamily::Linux);
+        registry.unregister("cent7_64");
+        assert_eq!(registry.describe("cent7_64").label, "cent7_64");
+    }
+
+    #[test]
+    fn supplied_icons_replace_the_bundled_icon() {
+        let descriptor = PlatformDescriptor::new("Windows", OsFamily::Windows);
+        assert_eq!(descriptor.icon_path(), Some(":/images/os_windows.svg"));
+        assert_eq!(
+            descriptor.with_icon(":/custom.svg").icon_path(),
+            Some(":/custom.svg")
+        );
+    }
+}
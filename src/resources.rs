@@ -0,0 +1,24 @@
+use qt_core::QResource;
+use std::sync::Once;
+
+// the binary rcc file compiled from resources/pbgui_tree.qrc by the build script
+static RCC_DATA: &'static [u8] = include_bytes!(concat!(env!("OUT_DIR"), "/pbgui_tree.rcc"));
+
+static INIT: Once = Once::new();
+
+/// Register the crate's embedded qt resources (icons and images), making them
+/// available under `:/images/`. This is called by `DistributionTreeView::create`,
+/// and is safe to call any number of times; the resources are only registered once.
+///
+/// # Arguments
+/// * None
+///
+/// # Returns
+/// * None
+pub fn init_resources() {
+    INIT.call_once(|| unsafe {
+        if !QResource::register_resource_uchar(RCC_DATA.as_ptr()) {
+            log::error!("Unable to register the embedded pbgui_tree resources");
+        }
+    });
+}
@@ -0,0 +1,56 @@
+/// The selection mode of the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// Only a single row may be selected at a time (the default)
+    Single,
+    /// Multiple rows may be selected using shift and ctrl
+    Extended,
+}
+
+impl Default for SelectionMode {
+    fn default() -> Self {
+        SelectionMode::Single
+    }
+}
+
+/// A selected row in the tree. Depending upon the depth of the row,
+/// the version and platform may or may not be present.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DistributionSelection {
+    pub package: String,
+    pub version: Option<String>,
+    pub platform: Option<String>,
+    pub site: String,
+}
+
+impl DistributionSelection {
+    /// New up a DistributionSelection
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    /// * `version` - The version of the package, if selected
+    /// * `platform` - The platform of the version, if selected
+    /// * `site` - The site which was current at the time of selection
+    ///
+    /// # Returns
+    /// * DistributionSelection instance
+    pub fn new<P, S>(package: P, version: Option<String>, platform: Option<String>, site: S) -> Self
+    where
+        P: Into<String>,
+        S: Into<String>,
+    {
+        Self {
+            package: package.into(),
+            version,
+            platform,
+            site: site.into(),
+        }
+    }
+
+    /// Retrieve the distribution name (eg maya-2020.1), if a version is selected
+    pub fn distribution(&self) -> Option<String> {
+        self.version
+            .as_ref()
+            .map(|version| format!("{}-{}", self.package, version))
+    }
+}
@@ -0,0 +1,52 @@
+/// The actions which may be triggered via keyboard shortcuts. Each action
+/// has a default key sequence, which may be remapped by the host application
+/// via `DistributionTreeView::set_shortcut`.
+///
+/// In addition to these, the tree supports type-ahead: typing while the tree
+/// has focus jumps to the first package starting with the typed text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutAction {
+    /// Open the package filter and give it focus. Default: `Ctrl+F`
+    OpenFilter,
+    /// Clear and close the package filter. Default: `Esc`
+    CloseFilter,
+    /// Jump from the package filter to the first matching package. Default: `Return`
+    JumpToMatch,
+    /// Fully expand the current package. Default: `*`
+    ExpandPackage,
+    /// Undo the most recent pin change. Default: `Ctrl+Z`
+    Undo,
+    /// Redo the most recently undone pin change. Default: `Ctrl+Shift+Z`
+    Redo,
+}
+
+impl ShortcutAction {
+    /// All of the available actions
+    pub const ALL: [ShortcutAction; 6] = [
+        ShortcutAction::OpenFilter,
+        ShortcutAction::CloseFilter,
+        ShortcutAction::JumpToMatch,
+        ShortcutAction::ExpandPackage,
+        ShortcutAction::Undo,
+        ShortcutAction::Redo,
+    ];
+
+    /// Retrieve the default key sequence for the action, in the portable
+    /// text format understood by QKeySequence.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Key sequence as a str
+    pub fn default_key_sequence(&self) -> &'static str {
+        match self {
+            ShortcutAction::OpenFilter => "Ctrl+F",
+            ShortcutAction::CloseFilter => "Esc",
+            ShortcutAction::JumpToMatch => "Return",
+            ShortcutAction::ExpandPackage => "*",
+            ShortcutAction::Undo => "Ctrl+Z",
+            ShortcutAction::Redo => "Ctrl+Shift+Z",
+        }
+    }
+}
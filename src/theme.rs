@@ -0,0 +1,158 @@
+use crate::widget::WidgetError;
+use std::fmt;
+use std::path::PathBuf;
+
+const DARK_STYLE_STR: &'static str = include_str!("../resources/tree.qss");
+const LIGHT_STYLE_STR: &'static str = include_str!("../resources/tree_light.qss");
+
+/// The set of icons used by the tree. Each theme pairs its stylesheet
+/// with an icon set which remains legible against its background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconSet {
+    /// Light icons, for use against a dark background
+    Dark,
+    /// Dark icons, for use against a light background
+    Light,
+}
+
+impl IconSet {
+    /// The icon of the filter button when the filter is hidden
+    pub fn filter_off(&self) -> &'static str {
+        match self {
+            IconSet::Dark => ":/images/filter_white_sm.svg",
+            IconSet::Light => ":/images/filter_grey_sm.svg",
+        }
+    }
+
+    /// The icon of the filter button when the filter is visible
+    pub fn filter_on(&self) -> &'static str {
+        ":/images/filter_blue_sm.svg"
+    }
+
+    /// The icon displayed next to the site combobox
+    pub fn site(&self) -> &'static str {
+        match self {
+            IconSet::Dark => ":/images/world.svg",
+            IconSet::Light => ":/images/world_dark.svg",
+        }
+    }
+
+    /// The icon displayed next to each package
+    pub fn package(&self) -> &'static str {
+        match self {
+            IconSet::Dark => ":/images/package_md.png",
+            IconSet::Light => ":/images/package_md_dark.svg",
+        }
+    }
+
+    /// The icon marking pinned versions
+    pub fn pin(&self) -> &'static str {
+        ":/images/pin_grey.svg"
+    }
+}
+
+/// Errors raised when applying a theme to the tree
+#[derive(Debug)]
+pub enum ThemeError {
+    /// The stylesheet of a file based theme could not be read
+    Io(std::io::Error),
+    /// The tree's widgets have been destroyed
+    Widget(WidgetError),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(err) => write!(f, "unable to read the theme: {}", err),
+            ThemeError::Widget(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThemeError::Io(err) => Some(err),
+            ThemeError::Widget(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for ThemeError {
+    fn from(err: std::io::Error) -> Self {
+        ThemeError::Io(err)
+    }
+}
+
+impl From<WidgetError> for ThemeError {
+    fn from(err: WidgetError) -> Self {
+        ThemeError::Widget(err)
+    }
+}
+
+/// The look of the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Theme {
+    /// The built in dark theme (the default)
+    Dark,
+    /// The built in light theme
+    Light,
+    /// A user supplied stylesheet, which is reloaded whenever the file changes,
+    /// along with the icon set which matches it.
+    File { path: PathBuf, icons: IconSet },
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::Dark
+    }
+}
+
+impl Theme {
+    /// New up a Theme from a user supplied qss file, using the dark icon set
+    ///
+    /// # Arguments
+    /// * `path` - The path to the qss file
+    ///
+    /// # Returns
+    /// * Theme instance
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> Self {
+        Theme::File {
+            path: path.into(),
+            icons: IconSet::Dark,
+        }
+    }
+
+    /// Retrieve the icon set of the theme
+    pub fn icons(&self) -> IconSet {
+        match self {
+            Theme::Dark => IconSet::Dark,
+            Theme::Light => IconSet::Light,
+            Theme::File { icons, .. } => *icons,
+        }
+    }
+
+    /// Retrieve the path of the stylesheet, if the theme is file based
+    pub fn path(&self) -> Option<&PathBuf> {
+        match self {
+            Theme::File { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the stylesheet of the theme, reading it from disk if the theme
+    /// is file based.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping the stylesheet, or an io error
+    pub fn stylesheet(&self) -> std::io::Result<String> {
+        match self {
+            Theme::Dark => Ok(DARK_STYLE_STR.to_string()),
+            Theme::Light => Ok(LIGHT_STYLE_STR.to_string()),
+            Theme::File { path, .. } => std::fs::read_to_string(path),
+        }
+    }
+}
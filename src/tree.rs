@@ -1,15 +1,40 @@
-use crate::api::{ClientProxy, PackratDb};
+use crate::cache::CacheStats;
+use crate::callback::Callbacks;
+use crate::columns::Column;
+use crate::compare::SiteComparison;
+use crate::edit::{PinPreview, PinRequest};
+use crate::filter::{FilterMode, MatchCount};
+use crate::grouping::Grouping;
+use crate::hierarchy::Hierarchy;
+use crate::history::HistoryEntry;
 use crate::inner_tree::InnerTreeView;
-use packybara::traits::*;
-use qt_core::{QModelIndex, QString, Signal, SlotOfBool, SlotOfQModelIndex, SlotOfQString};
+use crate::live::{LiveUpdate, DEFAULT_CHANNEL};
+use crate::loading::LoadingMode;
+use crate::overlay::{LoadFailure, LoadRequest, OverlayState, OverlayText};
+use crate::platform::{PlatformDescriptor, PlatformRegistry};
+use crate::resources::init_resources;
+use crate::selection::{DistributionSelection, SelectionMode};
+use crate::shortcuts::ShortcutAction;
+use crate::theme::{Theme, ThemeError};
+use crate::undo::{PinChange, UndoStack};
+use crate::widget::WidgetError;
+use qt_core::{
+    QItemSelection, QModelIndex, QPoint, QString, Signal, Slot, SlotOfBool, SlotOfIntInt,
+    SlotOfQItemSelectionQItemSelection, SlotOfQModelIndex, SlotOfQPoint, SlotOfQString,
+};
 use qt_gui::{QStandardItem, QStandardItemModel};
 use qt_widgets::{
     cpp_core::{MutPtr, Ref, StaticUpcast},
-    QPushButton, QWidget,
+    QDialog, QFrame, QPushButton, QWidget,
 };
 
 use rustqt_utils::{enclose, ToQStringOwned};
+use std::collections::BTreeMap;
 use std::rc::Rc;
+use std::time::Duration;
+
+/// The depth to which the expand all button expands the tree
+const DEFAULT_EXPAND_DEPTH: i32 = 1;
 
 /// DistributionTreeView provides a tree of packages -> versions -> platforms
 /// per site, along with a set of signals/slots that handle expanding &
@@ -21,51 +46,79 @@ pub struct DistributionTreeView<'a> {
     collapsed: SlotOfQModelIndex<'a>,
     filter_visible: SlotOfBool<'a>,
     filter_slot: SlotOfQString<'a>,
+    selection_slot: SlotOfQItemSelectionQItemSelection<'a>,
+    pressed: SlotOfQModelIndex<'a>,
+    track_drag: Slot<'a>,
+    open_filter: Slot<'a>,
+    close_filter: Slot<'a>,
+    jump_to_match: Slot<'a>,
+    expand_package: Slot<'a>,
+    undo: Slot<'a>,
+    redo: Slot<'a>,
+    expand_all: SlotOfBool<'a>,
+    collapse_all: SlotOfBool<'a>,
+    site_changed: SlotOfQString<'a>,
+    live_update: Slot<'a>,
+    spinner: Slot<'a>,
+    deferred_load: Slot<'a>,
+    theme_file_changed: SlotOfQString<'a>,
+    column_toggled: Vec<SlotOfBool<'a>>,
+    context_menu: SlotOfQPoint<'a>,
+    history_selected: SlotOfIntInt<'a>,
+    comparison_selected: SlotOfIntInt<'a>,
+    retry: SlotOfBool<'a>,
+}
+
+impl<'a> Default for DistributionTreeView<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-// filter using is any
-fn is_not_any(item: &str) -> Option<&str> {
-    if item == "any" {
-        None
-    } else {
-        Some(item)
+impl<'a> Drop for DistributionTreeView<'a> {
+    // A tree which was never placed owns its frame, which would otherwise leak
+    fn drop(&mut self) {
+        self.view.delete_unattached();
     }
 }
 
 impl<'a> DistributionTreeView<'a> {
-    /// create a treeview given a main window of any type that can be cast to QWidget
+    /// create a treeview given a main window of any type that can be cast to
+    /// QWidget, adding it to the main window's layout
     ///
     /// # Arguments
     /// * `parent_widget` - The parent of the tree view
     ///
     /// # Returns
-    /// * `DistributionTreeView instance
-    pub fn create<T>(parent_widget: MutPtr<T>) -> DistributionTreeView<'a>
+    /// * Result wrapping the DistributionTreeView instance, or
+    ///   `WidgetError::MissingLayout` if the parent has no layout
+    pub fn create<T>(parent_widget: MutPtr<T>) -> Result<DistributionTreeView<'a>, WidgetError>
     where
         T: StaticUpcast<QWidget>,
     {
+        let dtv = Self::new();
+        dtv.view.attach(parent_widget)?;
+        Ok(dtv)
+    }
+
+    /// create a treeview without a parent. Retrieve its widget via `widget()`
+    /// in order to place it in a layout, splitter or dock. A widget which has
+    /// not been placed is deleted when the tree is dropped.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * DistributionTreeView instance
+    pub fn new() -> DistributionTreeView<'a> {
+        init_resources();
         unsafe {
-            let treeview = Rc::new(InnerTreeView::create(parent_widget));
+            let treeview = Rc::new(InnerTreeView::create());
 
             //let tv = treeview.clone();
             let filter_slot =
-                SlotOfQString::new(enclose! { (treeview) move |new_str: Ref<QString>| {
-                    let model_ptr = treeview.model();
-                    if new_str.to_std_string() == "" {
-                        for cnt in (0..model_ptr.row_count_0a()).rev() {
-                            treeview.set_row_hidden(cnt,  false)
-                        }
-                    } else {
-                        for cnt in (0..model_ptr.row_count_0a()).rev() {
-                            let item = model_ptr.item_2a(cnt, 0);
-                            let txt = item.text();
-                            if txt.contains_q_string(new_str) {
-                                treeview.set_row_hidden(cnt, false)
-                            } else {
-                                treeview.set_row_hidden(cnt, true)
-                            }
-                        }
-                    }
+                SlotOfQString::new(enclose! { (treeview) move |_new_str: Ref<QString>| {
+                    treeview.apply_filter();
                 }});
             let dtv = DistributionTreeView {
                 view: treeview.clone(),
@@ -75,55 +128,16 @@ impl<'a> DistributionTreeView<'a> {
                 // }),
                 expanded: SlotOfQModelIndex::new(
                     enclose! { (treeview) move |idx: Ref<QModelIndex>| {
-                        let model = treeview.model();
-                        let row_cnt = model.row_count_1a(idx);
-                        if  row_cnt > 1 { return; }
-
-                        // what if we only have 1 item? Lets make sure that it isnt
-                        // an intended child (eg a single version or platform)
-                        let child = idx.child(0,0);
-                        if !child.is_valid() || model.item_from_index(child.as_ref()).text().to_std_string() != "" {
-                            return;
-                        }
-
-                        let item = model.item_from_index(idx);
-                        let item_str = item.text().to_std_string();
-
-                        let client = ClientProxy::connect().expect("Unable to connect via ClientProxy");
-                        let mut db = PackratDb::new(client);
-
-                        // we are a child of the root. Our parent is not "valid"
-                        if idx.parent().is_valid() == false {
-                            let results = db
-                                .find_all_distributions()
-                                .package(&item_str)
-                                .query()
-                                .expect("unable to find_all_distributions");
-                            let results = results.iter().map(|s| s.version.as_str()).collect::<Vec<_>>();
-                            if results.len() > 0 {
-                                treeview.model().remove_rows_3a(0,1, idx);
-                                treeview.set_children(item, results, true);
-                            }
-                        } else {
-                            // if we are not the child of the root, we must be the version, revealing
-                            // the platform
-                            let results = db
-                                .find_all_platforms()
-                                .query()
-                                .expect("unable to find_all_platforms");
-                            let results = results.iter().filter_map(|s| is_not_any(s.name.as_str())).collect::<Vec<_>>();
-                            if results.len() > 0 {
-                                treeview.model().remove_rows_3a(0,1, idx);
-                                treeview.set_children(item, results, false);
-                            }
-                        }
+                        treeview.request_children(idx);
                     }},
                 ),
 
                 collapsed: SlotOfQModelIndex::new(
                     enclose! { (treeview) move |idx: Ref<QModelIndex>| {
-                        if treeview.model().row_count_1a(idx) == 1 {
-                            treeview.set_row_hidden(idx.row(), false);
+                        if let Ok(model) = treeview.model() {
+                            if model.row_count_1a(idx) == 1 {
+                                treeview.set_row_hidden(idx.row(), false);
+                            }
                         }
                     }},
                 ),
@@ -131,29 +145,356 @@ impl<'a> DistributionTreeView<'a> {
                     treeview.set_filter_visibility(vis);
                 }}),
                 filter_slot,
+                selection_slot: SlotOfQItemSelectionQItemSelection::new(
+                    enclose! { (treeview) move |_selected: Ref<QItemSelection>, _deselected: Ref<QItemSelection>| {
+                        let selections = treeview.selected_distributions();
+                        treeview.selection_changed().emit(&selections);
+                    }},
+                ),
+                pressed: SlotOfQModelIndex::new(
+                    enclose! { (treeview) move |_idx: Ref<QModelIndex>| {
+                        treeview.press();
+                    }},
+                ),
+                track_drag: Slot::new(enclose! { (treeview) move || {
+                    treeview.track_drag();
+                }}),
+                open_filter: Slot::new(enclose! { (treeview) move || {
+                    treeview.open_filter();
+                }}),
+                close_filter: Slot::new(enclose! { (treeview) move || {
+                    treeview.close_filter();
+                }}),
+                jump_to_match: Slot::new(enclose! { (treeview) move || {
+                    treeview.jump_to_first_match();
+                }}),
+                expand_package: Slot::new(enclose! { (treeview) move || {
+                    treeview.expand_current_package();
+                }}),
+                undo: Slot::new(enclose! { (treeview) move || {
+                    if let Err(err) = treeview.undo() {
+                        log::warn!("unable to undo pin change: {}", err);
+                    }
+                }}),
+                redo: Slot::new(enclose! { (treeview) move || {
+                    if let Err(err) = treeview.redo() {
+                        log::warn!("unable to redo pin change: {}", err);
+                    }
+                }}),
+                expand_all: SlotOfBool::new(enclose! { (treeview) move |_checked: bool| {
+                    treeview.expand_all(DEFAULT_EXPAND_DEPTH);
+                }}),
+                collapse_all: SlotOfBool::new(enclose! { (treeview) move |_checked: bool| {
+                    treeview.collapse_all();
+                }}),
+                site_changed: SlotOfQString::new(
+                    enclose! { (treeview) move |_site: Ref<QString>| {
+                        treeview.request_site_update();
+                    }},
+                ),
+                live_update: Slot::new(enclose! { (treeview) move || {
+                    treeview.apply_pending_live_updates();
+                }}),
+                spinner: Slot::new(enclose! { (treeview) move || {
+                    treeview.advance_spinner();
+                }}),
+                deferred_load: Slot::new(enclose! { (treeview) move || {
+                    treeview.run_deferred_load();
+                }}),
+                theme_file_changed: SlotOfQString::new(
+                    enclose! { (treeview) move |_path: Ref<QString>| {
+                        if let Err(err) = treeview.reload_theme() {
+                            log::warn!("unable to reload theme: {}", err);
+                        }
+                    }},
+                ),
+                column_toggled: Column::ALL
+                    .iter()
+                    .map(|column| {
+                        let column = *column;
+                        SlotOfBool::new(enclose! { (treeview) move |visible: bool| {
+                            treeview.set_column_visible(column, visible);
+                        }})
+                    })
+                    .collect(),
+                context_menu: SlotOfQPoint::new(enclose! { (treeview) move |pos: Ref<QPoint>| {
+                    treeview.show_context_menu(pos);
+                }}),
+                retry: SlotOfBool::new(enclose! { (treeview) move |_checked: bool| {
+                    if let Err(err) = treeview.retry() {
+                        log::warn!("unable to retry the failed load: {}", err);
+                    }
+                }}),
+                history_selected: SlotOfIntInt::new(
+                    enclose! { (treeview) move |row: i32, _column: i32| {
+                        treeview.reveal_history_entry(row);
+                    }},
+                ),
+                comparison_selected: SlotOfIntInt::new(
+                    enclose! { (treeview) move |row: i32, _column: i32| {
+                        treeview.reveal_comparison_entry(row);
+                    }},
+                ),
             };
 
-            // Set up signals & slots
-            //treeview.view().clicked().connect(&dtv.clicked);
-            treeview.view().expanded().connect(&dtv.expanded);
-            treeview.view().collapsed().connect(&dtv.collapsed);
-            treeview.filter().text_changed().connect(&dtv.filter_slot);
-
-            dtv.filter_check_box()
-                .toggled()
-                .connect(&dtv.filter_visible);
+            // the widgets were created along with the tree, so they are alive
+            if let Err(err) = dtv.connect_slots() {
+                log::warn!("unable to connect the tree's slots: {}", err);
+            }
             dtv
         }
     }
+
+    // Set up signals & slots
+    unsafe fn connect_slots(&self) -> Result<(), WidgetError> {
+        let treeview = &self.view;
+        //treeview.view()?.clicked().connect(&self.clicked);
+        treeview.view()?.expanded().connect(&self.expanded);
+        treeview.view()?.collapsed().connect(&self.collapsed);
+        treeview.filter()?.text_changed().connect(&self.filter_slot);
+        treeview
+            .view()?
+            .selection_model()
+            .selection_changed()
+            .connect(&self.selection_slot);
+        treeview.view()?.pressed().connect(&self.pressed);
+        treeview.drag_timer()?.timeout().connect(&self.track_drag);
+        treeview
+            .shortcut(ShortcutAction::OpenFilter)?
+            .activated()
+            .connect(&self.open_filter);
+        treeview
+            .shortcut(ShortcutAction::CloseFilter)?
+            .activated()
+            .connect(&self.close_filter);
+        treeview
+            .shortcut(ShortcutAction::JumpToMatch)?
+            .activated()
+            .connect(&self.jump_to_match);
+        treeview
+            .shortcut(ShortcutAction::ExpandPackage)?
+            .activated()
+            .connect(&self.expand_package);
+        treeview
+            .shortcut(ShortcutAction::Undo)?
+            .activated()
+            .connect(&self.undo);
+        treeview
+            .shortcut(ShortcutAction::Redo)?
+            .activated()
+            .connect(&self.redo);
+        treeview
+            .expand_all_button()?
+            .clicked()
+            .connect(&self.expand_all);
+        treeview
+            .collapse_all_button()?
+            .clicked()
+            .connect(&self.collapse_all);
+        treeview
+            .combobox()?
+            .current_text_changed()
+            .connect(&self.site_changed);
+        treeview.live_timer()?.timeout().connect(&self.live_update);
+        treeview.spinner_timer()?.timeout().connect(&self.spinner);
+        treeview
+            .deferred_timer()?
+            .timeout()
+            .connect(&self.deferred_load);
+        treeview
+            .theme_watcher()?
+            .file_changed()
+            .connect(&self.theme_file_changed);
+        for (column, slot) in Column::ALL.iter().zip(self.column_toggled.iter()) {
+            treeview.column_action(*column)?.toggled().connect(slot);
+        }
+
+        treeview
+            .view()?
+            .custom_context_menu_requested()
+            .connect(&self.context_menu);
+        treeview
+            .history_table()?
+            .cell_clicked()
+            .connect(&self.history_selected);
+        treeview
+            .comparison_table()?
+            .cell_clicked()
+            .connect(&self.comparison_selected);
+        treeview.retry_button()?.clicked().connect(&self.retry);
+
+        treeview
+            .filter_cb()?
+            .toggled()
+            .connect(&self.filter_visible);
+        Ok(())
+    }
+
+    /// Retrieve the tree's top level widget, so that the host may place it in
+    /// any layout, splitter or dock. The widget is reparented when placed.
+    ///
+    /// The widget's ownership follows its parent: once it has a parent, Qt
+    /// deletes it along with that parent, but if it has none when the
+    /// DistributionTreeView is dropped, the drop deletes it. A layout only
+    /// reparents its widgets once it is installed on a widget, so keep the
+    /// DistributionTreeView alive until the frame has been given a parent.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping a MutPtr to the QFrame, or `WidgetError::Destroyed`
+    pub fn widget(&self) -> Result<MutPtr<QFrame>, WidgetError> {
+        self.view.widget()
+    }
+
+    /// Determine whether the tree's widgets have been destroyed by Qt, (eg
+    /// along with a parent). Once destroyed, setters do nothing, getters
+    /// which consult the widgets return defaults, and fallible methods return
+    /// `WidgetError::Destroyed`.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_destroyed(&self) -> bool {
+        self.view.is_destroyed()
+    }
+
+    // Retrieve the inner view, or WidgetError::Destroyed once its widgets
+    // have been destroyed, after which none of them may be touched
+    fn live(&self) -> Result<&InnerTreeView, WidgetError> {
+        if self.is_destroyed() {
+            Err(WidgetError::Destroyed)
+        } else {
+            Ok(&self.view)
+        }
+    }
+
+    /// Set how the text typed into the package filter is matched against
+    /// package names
+    ///
+    /// # Arguments
+    /// * `mode` - The FilterMode
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_filter_mode(&self, mode: FilterMode) {
+        if let Ok(view) = self.live() {
+            view.set_filter_mode(mode);
+        }
+    }
+
+    /// Retrieve how the package filter matches package names
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * FilterMode
+    pub fn filter_mode(&self) -> FilterMode {
+        self.live()
+            .map(|view| view.filter_mode())
+            .unwrap_or_default()
+    }
+
+    /// Retrieve the number of packages matching the filter (eg "12 of 843
+    /// packages"), as displayed beside the filter. When packages are grouped,
+    /// every package is counted; otherwise, only those which have been loaded.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MatchCount
+    pub fn match_count(&self) -> MatchCount {
+        self.view.match_count()
+    }
+
+    /// Enable or disable highlighting the text matching the filter within
+    /// each visible package. Highlighting is enabled by default.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether matches are highlighted
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_highlight_matches(&self, enabled: bool) {
+        if let Ok(view) = self.live() {
+            view.set_highlight_matches(enabled);
+        }
+    }
+
+    /// Determine whether the text matching the filter is highlighted
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn highlight_matches(&self) -> bool {
+        self.view.highlight_matches()
+    }
+
+    /// Set the background color of highlighted matches
+    ///
+    /// # Arguments
+    /// * `color` - A color understood by qt's rich text, eg `#b5762a` or `orange`
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_highlight_color(&self, color: &str) {
+        if let Ok(view) = self.live() {
+            view.set_highlight_color(color);
+        }
+    }
+
+    /// Retrieve the background color of highlighted matches
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * String
+    pub fn highlight_color(&self) -> String {
+        self.view.highlight_color()
+    }
+
+    /// Retrieve the sites in the site combobox
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of site names
+    pub fn sites(&self) -> Vec<String> {
+        self.live().map(|view| view.sites()).unwrap_or_default()
+    }
+
+    /// Retrieve the currently selected site
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The name of the site
+    pub fn current_site(&self) -> String {
+        self.live()
+            .map(|view| view.current_site())
+            .unwrap_or_default()
+    }
+
     /// Retrieve the Filter button (which is acting as a checkbox)
     ///
     /// # Arguments
     /// * None
     ///
     /// # Returns
-    /// * mutable pointer to the Filter QPushButton instance
-    pub fn filter_check_box(&self) -> MutPtr<QPushButton> {
-        self.view.filter_cb()
+    /// * Result wrapping a mutable pointer to the Filter QPushButton instance,
+    ///   or `WidgetError::Destroyed`
+    pub fn filter_check_box(&self) -> Result<MutPtr<QPushButton>, WidgetError> {
+        self.live()?.filter_cb()
     }
 
     /// Set the stylesheet to the internal stylesheet
@@ -164,120 +505,1128 @@ impl<'a> DistributionTreeView<'a> {
     /// # Returns
     /// * None
     pub fn set_default_stylesheet(&self) {
-        self.view.set_default_stylesheet();
+        if let Ok(view) = self.live() {
+            view.set_default_stylesheet();
+        }
     }
 
-    /// Retreive the model from the view
+    /// Apply a theme to the tree, setting its stylesheet and matching icons.
+    /// A `Theme::File` is watched, and reloaded whenever the file changes.
     ///
-    /// # Aeguments
-    /// * None
+    /// # Arguments
+    /// * `theme` - The Theme to apply
     ///
     /// # Returns
-    /// * A mutable pointer to the QStandardItemModel
-    pub fn model(&self) -> MutPtr<QStandardItemModel> {
-        self.view.model()
+    /// * Ok(()), `ThemeError::Io` if a file based theme cannot be read, or
+    ///   `ThemeError::Widget` if the tree has been destroyed
+    pub fn set_theme(&self, theme: Theme) -> Result<(), ThemeError> {
+        self.live()?.set_theme(theme)
     }
 
-    /// Retrieve the clicked Signal so that we may connect it to a slot
-    pub fn clicked(&self) -> Signal<(*const QModelIndex,)> {
-        self.view.view().clicked()
+    /// Retrieve the current theme
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Theme
+    pub fn theme(&self) -> Theme {
+        self.live().map(|view| view.theme()).unwrap_or_default()
     }
-    /// Given a type that implements ToQstringOwned, append a distribution.
+
+    /// Replace the registry used to map platform names (eg `cent7_64`) to
+    /// human friendly labels and OS icons.
     ///
     /// # Arguments
-    /// * `input` - Instance of any type that implements the ToQStringOwned trait.
-    /// (this includes &str, String and QString)
+    /// * `registry` - The PlatformRegistry
     ///
     /// # Returns
     /// * None
-    pub fn add_package<T: ToQStringOwned>(&self, input: T) {
-        self.view.add_package(input);
+    pub fn set_platform_registry(&self, registry: PlatformRegistry) {
+        if let Ok(view) = self.live() {
+            view.set_platform_registry(registry);
+        }
+    }
+
+    /// Retrieve a copy of the registry used to describe platforms
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * PlatformRegistry
+    pub fn platform_registry(&self) -> PlatformRegistry {
+        self.view.platform_registry()
     }
 
-    /// Clear the list of packages
+    /// Register the label and icon displayed for a platform
     ///
     /// # Arguments
+    /// * `name` - The name of the platform as stored in the database
+    /// * `descriptor` - The PlatformDescriptor
+    ///
+    /// # Returns
     /// * None
+    pub fn register_platform(&self, name: &str, descriptor: PlatformDescriptor) {
+        if let Ok(view) = self.live() {
+            view.register_platform(name, descriptor);
+        }
+    }
+
+    /// Switch multi column mode on or off. In multi column mode a sortable
+    /// header is displayed, along with columns showing the latest version and
+    /// version count of each package, and the pinned site count and platform
+    /// count of each version. Users may toggle the visibility of the columns
+    /// via the header's context menu. The columns are rebuilt over the extant
+    /// packages, which are kept, along with their loaded versions and platforms.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether multi column mode is enabled
     ///
     /// # Returns
     /// * None
-    pub fn clear_packages(&self) {
-        self.view.clear_packages();
+    pub fn set_multi_column(&self, enabled: bool) {
+        if let Ok(view) = self.live() {
+            view.set_multi_column(enabled);
+        }
     }
 
-    /// Clear the tree selection, if there is any.
+    /// Retrieve whether multi column mode is enabled
     ///
     /// # Arguments
     /// * None
     ///
     /// # Returns
+    /// * bool
+    pub fn is_multi_column(&self) -> bool {
+        self.live()
+            .map(|view| view.is_multi_column())
+            .unwrap_or_default()
+    }
+
+    /// Show or hide one of the optional columns displayed in multi column mode
+    ///
+    /// # Arguments
+    /// * `column` - The Column
+    /// * `visible` - Whether the column is visible
+    ///
+    /// # Returns
     /// * None
-    pub fn clear_selection(&self) {
-        self.view.clear_selection()
+    pub fn set_column_visible(&self, column: Column, visible: bool) {
+        if let Ok(view) = self.live() {
+            view.set_column_visible(column, visible);
+        }
     }
 
-    /// Given a vector of a type that implements the ToQstringOwned trait, set the packages
-    /// to match the list.
+    /// Retrieve whether one of the optional columns is visible. Optional columns
+    /// are never visible outside of multi column mode.
     ///
     /// # Arguments
-    /// * `inputs` - A vecctor of package names (&str or String or QString or...)
+    /// * `column` - The Column
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_column_visible(&self, column: Column) -> bool {
+        self.live()
+            .map(|view| view.is_column_visible(column))
+            .unwrap_or_default()
+    }
+
+    /// Replace the hierarchy describing the levels of the tree (eg
+    /// site → package → version), clearing the extant items. Reload them via
+    /// `load_packages`, which queries the items of the top level. Multi column
+    /// mode, site comparison and live updates annotate packages only when they
+    /// are the top level.
+    ///
+    /// # Arguments
+    /// * `hierarchy` - The Hierarchy
     ///
     /// # Returns
     /// * None
-    pub fn set_packages<T: ToQStringOwned>(&self, inputs: Vec<T>) {
-        self.view.set_packages(inputs);
+    pub fn set_hierarchy(&self, hierarchy: Hierarchy) {
+        if let Ok(view) = self.live() {
+            view.set_hierarchy(hierarchy);
+        }
     }
 
-    /// Add a child to the provided parent.
+    /// Retrieve the hierarchy describing the levels of the tree
     ///
     /// # Arguments
-    /// * `parent` - a mutable pointer to a QStandardItem rep of a package
-    /// * `child` - a disribution version, represented by any type implementing the ToQStringOwned trait.
+    /// * None
+    ///
+    /// # Returns
+    /// * Hierarchy
+    pub fn hierarchy(&self) -> Hierarchy {
+        self.view.hierarchy()
+    }
+
+    /// Bucket the top level packages into collapsible groups (eg by the
+    /// prefix `houdini`, by a host supplied callback, or by whether they are
+    /// pinned at the current site), or display them ungrouped when None. The
+    /// extant packages are preserved, and the filter hides groups without
+    /// any matching packages. Grouping requires the hierarchy to start with packages.
+    ///
+    /// # Arguments
+    /// * `grouping` - How packages are grouped, if at all
     ///
     /// # Returns
+    /// * Ok(()), or an error if the hierarchy cannot be grouped or the
+    ///   metadata used to group the packages cannot be queried
+    pub fn set_grouping(
+        &self,
+        grouping: Option<Grouping>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.live()?.set_grouping(grouping)
+    }
+
+    /// Retrieve how packages are grouped, if at all
+    ///
+    /// # Arguments
     /// * None
-    pub fn add_child<I>(&self, parent: MutPtr<QStandardItem>, child: I)
-    where
-        I: ToQStringOwned,
-    {
-        self.view.add_child(parent, child);
+    ///
+    /// # Returns
+    /// * Option wrapping the Grouping
+    pub fn grouping(&self) -> Option<Grouping> {
+        self.view.grouping()
     }
 
-    #[allow(dead_code)]
-    /// Set combobox sites, replacing any extant sites
+    /// Retrieve the groups, along with the packages within each. This is
+    /// empty unless packages are grouped.
     ///
     /// # Arguments
-    /// * `items` - Vector of items
+    /// * None
+    ///
+    /// # Returns
+    /// * Map of group name to packages
+    pub fn groups(&self) -> BTreeMap<String, Vec<String>> {
+        self.view.groups()
+    }
+
+    /// Show or hide an additional level beneath each platform, listing the
+    /// roles and levels at which the version is pinned for the platform at
+    /// the current site (eg `lighting @ dev01`).
+    ///
+    /// # Arguments
+    /// * `show` - Whether to show the pins
     ///
     /// # Returns
     /// * None
-    pub fn set_sites<'c, I>(&self, items: Vec<I>, current: I)
-    where
-        I: AsRef<str>,
-    {
-        self.view.set_sites(items, current);
+    pub fn set_show_pins(&self, show: bool) {
+        if let Ok(view) = self.live() {
+            view.set_show_pins(show);
+        }
     }
 
-    #[allow(dead_code)]
-    /// Remove all sites from the combobox
+    /// Retrieve whether pins are shown beneath each platform
     ///
     /// # Arguments
     /// * None
     ///
-    /// # Returns None
-    pub fn remove_sites(&self) {
-        self.view.remove_sites();
+    /// # Returns
+    /// * bool
+    pub fn show_pins(&self) -> bool {
+        self.view.show_pins()
     }
 
-    /// Change the max number of items displayed in the combobox's dropdown
-    /// list
+    /// Compare the pins of two of the sites in the site combobox. The
+    /// differences are listed side by side in a table beside the tree, with a
+    /// column of versions for each site; clicking a difference selects its
+    /// version in the tree. Packages whose pins differ are highlighted, with
+    /// the differences listed in the tooltips of the packages and of the
+    /// versions involved.
     ///
     /// # Arguments
-    /// * `max` - Maximum number of visible items in the comobobox's dropdown
+    /// * `first` - The name of the first site
+    /// * `second` - The name of the second site
+    ///
+    /// # Returns
+    /// * Ok(()) or an error if either site is unknown or the query fails
+    pub fn compare_sites(
+        &self,
+        first: &str,
+        second: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.live()?.compare_sites(first, second)
+    }
+
+    /// Leave comparison mode
+    ///
+    /// # Arguments
+    /// * None
     ///
     /// # Returns
     /// * None
-    pub fn set_cb_max_visible_items(&self, max: i32) {
-        self.view.set_cb_max_visible_items(max);
+    pub fn end_comparison(&self) {
+        if let Ok(view) = self.live() {
+            view.end_comparison();
+        }
+    }
+
+    /// Retrieve the current site comparison, if any
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some SiteComparison when comparing, None otherwise
+    pub fn comparison(&self) -> Option<SiteComparison> {
+        self.view.comparison()
+    }
+
+    /// When comparing sites, show only the packages whose pins differ
+    ///
+    /// # Arguments
+    /// * `only` - Whether to show only the packages which differ
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_show_only_differences(&self, only: bool) {
+        if let Ok(view) = self.live() {
+            view.set_show_only_differences(only);
+        }
+    }
+
+    /// Retrieve whether only the packages which differ are shown when comparing sites
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn show_only_differences(&self) -> bool {
+        self.view.show_only_differences()
+    }
+
+    /// Enable or disable edit mode. In edit mode, right clicking a version or
+    /// platform offers to create a pin: a role, level and platform chooser
+    /// previews the request (a dry run), and the pin is written once confirmed.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether edit mode is enabled
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_edit_mode(&self, enabled: bool) {
+        if let Ok(view) = self.live() {
+            view.set_edit_mode(enabled);
+        }
+    }
+
+    /// Retrieve whether edit mode is enabled
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn edit_mode(&self) -> bool {
+        self.view.edit_mode()
+    }
+
+    /// Set the user recorded as having made the pin changes written through
+    /// the tree, including those made by undo and redo. Pins may not be
+    /// written until an author has been set.
+    ///
+    /// # Arguments
+    /// * `author` - The name of the user
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_author(&self, author: &str) {
+        if let Ok(view) = self.live() {
+            view.set_author(author);
+        }
+    }
+
+    /// Retrieve the user recorded as having made pin changes, if one has been set
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Option wrapping the name of the user
+    pub fn author(&self) -> Option<String> {
+        self.view.author()
+    }
+
+    /// Validate a pin request and preview its outcome, without writing anything
+    ///
+    /// # Arguments
+    /// * `request` - The PinRequest
+    ///
+    /// # Returns
+    /// * Result wrapping the PinPreview, or an error describing why the request is invalid
+    pub fn preview_pin(
+        &self,
+        request: &PinRequest,
+    ) -> Result<PinPreview, Box<dyn std::error::Error>> {
+        self.live()?.preview_pin(request)
+    }
+
+    /// Validate and write a version pin within a transaction, updating the
+    /// tree's pin markers. Requests which would leave the pin unchanged are
+    /// rejected, as are requests made before an author has been set.
+    ///
+    /// # Arguments
+    /// * `request` - The PinRequest
+    /// * `comment` - The comment recorded with the change
+    ///
+    /// # Returns
+    /// * Result wrapping the PinPreview of the written pin, or an error
+    pub fn create_pin(
+        &self,
+        request: &PinRequest,
+        comment: &str,
+    ) -> Result<PinPreview, Box<dyn std::error::Error>> {
+        self.live()?.create_pin(request, comment)
+    }
+
+    /// Retrieve the callbacks invoked with each pin created through the tree
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Reference to the Callbacks
+    pub fn pin_created(&self) -> &Callbacks<PinPreview> {
+        self.view.pin_created()
+    }
+
+    /// Undo the most recent pin change made through the tree, restoring the
+    /// version previously pinned at its coordinates (or removing the pin).
+    /// Also bound to `ShortcutAction::Undo`.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping the undone PinChange (None if there was nothing to undo), or an error
+    pub fn undo(&self) -> Result<Option<PinChange>, Box<dyn std::error::Error>> {
+        self.live()?.undo()
+    }
+
+    /// Redo the most recently undone pin change. Also bound to `ShortcutAction::Redo`.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping the redone PinChange (None if there was nothing to redo), or an error
+    pub fn redo(&self) -> Result<Option<PinChange>, Box<dyn std::error::Error>> {
+        self.live()?.redo()
+    }
+
+    /// Retrieve a copy of the undo stack, for display by the host
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * UndoStack
+    pub fn undo_stack(&self) -> UndoStack {
+        self.view.undo_stack()
+    }
+
+    /// Retrieve the callbacks invoked with the undo stack whenever it changes
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Reference to the Callbacks
+    pub fn undo_stack_changed(&self) -> &Callbacks<UndoStack> {
+        self.view.undo_stack_changed()
+    }
+
+    /// Discard the recorded pin changes
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear_undo_stack(&self) {
+        if let Ok(view) = self.live() {
+            view.clear_undo_stack();
+        }
+    }
+
+    /// Present the pin history of a package in the history panel: which
+    /// versions were pinned when, by whom and in which changeset. The history
+    /// is also available by right clicking a package. Selecting an entry in the
+    /// panel highlights its version in the tree.
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    ///
+    /// # Returns
+    /// * Ok(()) or an error if the history could not be retrieved
+    pub fn show_history(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.live()?.show_history(package)
+    }
+
+    /// Retrieve the entries presented in the history panel, most recent first
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of HistoryEntry
+    pub fn history(&self) -> Vec<HistoryEntry> {
+        self.view.history()
+    }
+
+    /// Retrieve the package whose history is presented in the history panel, if any
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some package name, or None
+    pub fn history_package(&self) -> Option<String> {
+        self.view.history_package()
+    }
+
+    /// Retrieve the history panel. It is owned by the tree, but hosts may
+    /// restyle or reposition it.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping a MutPtr to the QDialog, or `WidgetError::Destroyed`
+    pub fn history_panel(&self) -> Result<MutPtr<QDialog>, WidgetError> {
+        self.live()?.history_panel()
+    }
+
+    /// Expand a package and select one of its versions, scrolling it into view
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    /// * `version` - The version to select
+    ///
+    /// # Returns
+    /// * true if the version was found, false otherwise
+    pub fn reveal_version(&self, package: &str, version: &str) -> bool {
+        self.live()
+            .map(|view| view.reveal_version(package, version))
+            .unwrap_or_default()
+    }
+
+    /// Retreive the model from the view
+    ///
+    /// # Aeguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Result wrapping a mutable pointer to the QStandardItemModel, or
+    ///   `WidgetError::Destroyed`
+    pub fn model(&self) -> Result<MutPtr<QStandardItemModel>, WidgetError> {
+        self.live()?.model()
+    }
+
+    /// Retrieve the clicked Signal so that we may connect it to a slot, or
+    /// `WidgetError::Destroyed`
+    pub fn clicked(&self) -> Result<Signal<(*const QModelIndex,)>, WidgetError> {
+        Ok(self.live()?.view()?.clicked())
+    }
+    /// Given a type that implements ToQstringOwned, append a distribution,
+    /// which may be expanded to load its versions. Packages which are already
+    /// present are ignored.
+    ///
+    /// # Arguments
+    /// * `input` - Instance of any type that implements the ToQStringOwned trait.
+    /// (this includes &str, String and QString)
+    ///
+    /// # Returns
+    /// * None
+    pub fn add_package<T: ToQStringOwned>(&self, input: T) {
+        if let Ok(view) = self.live() {
+            view.add_package(input);
+        }
+    }
+
+    /// Remove a package, along with any versions and platforms loaded beneath it
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    ///
+    /// # Returns
+    /// * Whether the package was present
+    pub fn remove_package(&self, package: &str) -> bool {
+        self.live()
+            .map(|view| view.remove_package(package))
+            .unwrap_or_default()
+    }
+
+    /// Remove every package, along with its children. The columns are preserved.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear(&self) {
+        if let Ok(view) = self.live() {
+            view.clear();
+        }
+    }
+
+    /// Clear the list of packages. Equivalent to `clear`.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear_packages(&self) {
+        if let Ok(view) = self.live() {
+            view.clear_packages();
+        }
+    }
+
+    /// Clear the tree selection, if there is any.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear_selection(&self) {
+        if let Ok(view) = self.live() {
+            view.clear_selection();
+        }
+    }
+
+    /// Set the selection mode of the tree. In `SelectionMode::Extended`, users
+    /// may shift and ctrl select multiple versions or platforms across packages.
+    ///
+    /// # Arguments
+    /// * `mode` - The SelectionMode to use
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_selection_mode(&self, mode: SelectionMode) {
+        if let Ok(view) = self.live() {
+            view.set_selection_mode(mode);
+        }
+    }
+
+    /// Retrieve the current selection mode of the tree
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * SelectionMode
+    pub fn selection_mode(&self) -> SelectionMode {
+        self.live()
+            .map(|view| view.selection_mode())
+            .unwrap_or_default()
+    }
+
+    /// Retrieve the currently selected rows
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of DistributionSelection, one per selected row
+    pub fn selected_distributions(&self) -> Vec<DistributionSelection> {
+        self.live()
+            .map(|view| view.selected_distributions())
+            .unwrap_or_default()
+    }
+
+    /// Retrieve the selection_changed callbacks so that we may connect to them.
+    /// The callbacks are invoked with the full, batched selection every time
+    /// the selection changes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// mytree.selection_changed().connect(|selections| {
+    ///     for selection in selections {
+    ///         println!("{:?}", selection);
+    ///     }
+    /// });
+    /// ```
+    pub fn selection_changed(&self) -> &Callbacks<Vec<DistributionSelection>> {
+        self.view.selection_changed()
+    }
+
+    /// Enable or disable dragging version and platform rows out of the tree.
+    /// Dragged rows are carried as mime data; see the `mime` module for
+    /// the format and for a helper to decode it on the drop side.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether dragging is enabled
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_drag_enabled(&self, enabled: bool) {
+        if let Ok(view) = self.live() {
+            view.set_drag_enabled(enabled);
+        }
+    }
+
+    /// Retrieve whether dragging rows out of the tree is enabled
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn drag_enabled(&self) -> bool {
+        self.view.drag_enabled()
+    }
+
+    /// Expand all of the visible packages to the supplied depth, loading the
+    /// required children in bulk. Loading is capped by `set_max_eager_nodes`
+    /// in order to keep the ui responsive; rows beyond the cap remain collapsed.
+    ///
+    /// # Arguments
+    /// * `depth` - The number of levels to expand. 1 reveals versions, 2 reveals platforms.
+    ///
+    /// # Returns
+    /// * The number of nodes loaded
+    pub fn expand_all(&self, depth: i32) -> usize {
+        self.live()
+            .map(|view| view.expand_all(depth))
+            .unwrap_or_default()
+    }
+
+    /// Collapse every item in the tree
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn collapse_all(&self) {
+        if let Ok(view) = self.live() {
+            view.collapse_all();
+        }
+    }
+
+    /// Set the LoadingMode used to fetch the versions of packages. In
+    /// `LoadingMode::Eager`, all of the distributions are fetched in a single
+    /// query and expansions are served from memory, unless a count of the
+    /// distributions exceeds the mode's maximum.
+    ///
+    /// # Arguments
+    /// * `mode` - The LoadingMode
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_loading_mode(&self, mode: LoadingMode) {
+        if let Ok(view) = self.live() {
+            view.set_loading_mode(mode);
+        }
+    }
+
+    /// Retrieve the LoadingMode used to fetch the versions of packages
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * LoadingMode
+    pub fn loading_mode(&self) -> LoadingMode {
+        self.view.loading_mode()
+    }
+
+    /// Determine whether expansions are being served from the eagerly loaded
+    /// index. This is false in lazy mode, before the first expansion, and
+    /// when eager loading has fallen back to lazy loading for a large site.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_indexed(&self) -> bool {
+        self.view.is_indexed()
+    }
+
+    /// Set the time to live of the cached results of database queries (packages,
+    /// versions per package, platforms and sites). A zero duration effectively
+    /// disables caching.
+    ///
+    /// # Arguments
+    /// * `ttl` - The time to live
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_cache_ttl(&self, ttl: Duration) {
+        self.view.set_cache_ttl(ttl);
+    }
+
+    /// Retrieve the time to live of the cached results of database queries
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Duration
+    pub fn cache_ttl(&self) -> Duration {
+        self.view.cache_ttl()
+    }
+
+    /// Retrieve the cache hit and miss statistics
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * CacheStats instance
+    pub fn cache_stats(&self) -> CacheStats {
+        self.view.cache_stats()
+    }
+
+    /// Reset the cache hit and miss statistics
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn reset_cache_stats(&self) {
+        self.view.reset_cache_stats();
+    }
+
+    /// Invalidate the cached versions of a package, so that they are queried
+    /// from the database the next time they are needed
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    ///
+    /// # Returns
+    /// * None
+    pub fn invalidate(&self, package: &str) {
+        if let Ok(view) = self.live() {
+            view.invalidate(package);
+        }
+    }
+
+    /// Invalidate all of the cached query results
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn invalidate_all(&self) {
+        if let Ok(view) = self.live() {
+            view.invalidate_all();
+        }
+    }
+
+    /// Query the packages from the database, via the cache, and replace the
+    /// extant packages with them
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Ok(()) or an error if the query fails
+    pub fn load_packages(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.live()?.load_packages()
+    }
+
+    /// Query the sites from the database, via the cache, and replace the
+    /// extant sites with them
+    ///
+    /// # Arguments
+    /// * `current` - The site to make current, or None for the first site
+    ///
+    /// # Returns
+    /// * Ok(()) or an error if the query fails
+    pub fn load_sites(&self, current: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        self.live()?.load_sites(current)
+    }
+
+    /// Determine whether the tree is loading from the database
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_loading(&self) -> bool {
+        self.view.is_loading()
+    }
+
+    /// Retrieve the callbacks invoked with true when the tree starts loading
+    /// from the database (eg packages, sites, the children of an expanded
+    /// item, or a site switch), and with false once it finishes, so that the
+    /// host may reflect the state in its status bar. The children of an
+    /// expanded item, and a site switch, are loaded once control returns to
+    /// the event loop, after their animated indicators have been painted.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * A reference to the Callbacks
+    pub fn loading_changed(&self) -> &Callbacks<bool> {
+        self.view.loading_changed()
+    }
+
+    /// Retrieve the last failed load (of the packages, the sites, or an
+    /// expansion of the whole tree), if it has yet to succeed. While a load
+    /// has failed, the error overlay is displayed in place of the tree. A
+    /// failure to load the children of an item is instead displayed beneath
+    /// the item, and retried by expanding it again.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Option wrapping the LoadFailure
+    pub fn load_failure(&self) -> Option<LoadFailure> {
+        self.view.load_failure()
+    }
+
+    /// Re-run the last failed load, if any. This is invoked by the retry
+    /// button of the error overlay.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Ok(()), or the error of the load, if it failed again
+    pub fn retry(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.live()?.retry()
+    }
+
+    /// Retrieve the callbacks invoked with the failed load as it is retried,
+    /// so that the host may respond (eg by reconnecting)
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * A reference to the Callbacks
+    pub fn retried(&self) -> &Callbacks<LoadRequest> {
+        self.view.retried()
+    }
+
+    /// Replace the text displayed in place of the tree when there are no
+    /// packages, no packages match the filter, or a load has failed
+    ///
+    /// # Arguments
+    /// * `text` - The OverlayText
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_overlay_text(&self, text: OverlayText) {
+        if let Ok(view) = self.live() {
+            view.set_overlay_text(text);
+        }
+    }
+
+    /// Retrieve the text displayed in place of the tree
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * OverlayText
+    pub fn overlay_text(&self) -> OverlayText {
+        self.view.overlay_text()
+    }
+
+    /// Retrieve the state of the overlay displayed in place of the tree, if any
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Option wrapping the OverlayState
+    pub fn overlay_state(&self) -> Option<OverlayState> {
+        self.view.overlay_state()
+    }
+
+    /// Start listening for live updates on the default channel. New packages and
+    /// versions are inserted into the tree, and pin markers updated, as
+    /// notifications arrive. See the `live` module for the notification format.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Ok(()) or an error if the listening connection cannot be established
+    pub fn start_live_updates(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.live()?.start_live_updates(DEFAULT_CHANNEL)
+    }
+
+    /// Start listening for live updates on the supplied channel
+    ///
+    /// # Arguments
+    /// * `channel` - The name of the channel to LISTEN on
+    ///
+    /// # Returns
+    /// * Ok(()) or an error if the listening connection cannot be established
+    pub fn start_live_updates_on(&self, channel: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.live()?.start_live_updates(channel)
+    }
+
+    /// Stop listening for live updates
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn stop_live_updates(&self) {
+        if let Ok(view) = self.live() {
+            view.stop_live_updates();
+        }
+    }
+
+    /// Determine whether the tree is listening for live updates
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_live(&self) -> bool {
+        self.view.is_live()
+    }
+
+    /// Retrieve the callbacks invoked after each live update has been applied to the tree
+    pub fn live_updated(&self) -> &Callbacks<LiveUpdate> {
+        self.view.live_updated()
+    }
+
+    /// Set the maximum number of nodes which a single call to `expand_all` may load
+    ///
+    /// # Arguments
+    /// * `max` - The maximum number of nodes
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_max_eager_nodes(&self, max: usize) {
+        if let Ok(view) = self.live() {
+            view.set_max_eager_nodes(max);
+        }
+    }
+
+    /// Retrieve the maximum number of nodes which a single call to `expand_all` may load
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * usize
+    pub fn max_eager_nodes(&self) -> usize {
+        self.view.max_eager_nodes()
+    }
+
+    /// Remap the key sequence which triggers one of the tree's keyboard shortcuts
+    ///
+    /// # Arguments
+    /// * `action` - The ShortcutAction to remap
+    /// * `key_sequence` - The key sequence in QKeySequence's portable text format (eg "Ctrl+Shift+F")
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_shortcut(&self, action: ShortcutAction, key_sequence: &str) {
+        if let Ok(view) = self.live() {
+            view.set_shortcut(action, key_sequence);
+        }
+    }
+
+    /// Retrieve the key sequence currently triggering one of the tree's keyboard shortcuts
+    ///
+    /// # Arguments
+    /// * `action` - The ShortcutAction of interest
+    ///
+    /// # Returns
+    /// * The key sequence in QKeySequence's portable text format
+    pub fn shortcut(&self, action: ShortcutAction) -> String {
+        self.live()
+            .map(|view| view.shortcut_key_sequence(action))
+            .unwrap_or_default()
+    }
+
+    /// Given a vector of a type that implements the ToQstringOwned trait, set the packages
+    /// to match the list, replacing any extant packages. Each package may be
+    /// expanded to load its versions. Only the packages which have changed are
+    /// inserted or removed, so the rest keep their loaded versions and
+    /// expansion state, allowing the list to be refreshed periodically.
+    /// Duplicates are ignored.
+    ///
+    /// # Arguments
+    /// * `inputs` - A vecctor of package names (&str or String or QString or...)
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_packages<T: ToQStringOwned>(&self, inputs: Vec<T>) {
+        if let Ok(view) = self.live() {
+            view.set_packages(inputs);
+        }
+    }
+
+    /// Add a child to the provided parent.
+    ///
+    /// # Arguments
+    /// * `parent` - a mutable pointer to a QStandardItem rep of a package
+    /// * `child` - a disribution version, represented by any type implementing the ToQStringOwned trait.
+    ///
+    /// # Returns
+    /// * None
+    pub fn add_child<I>(&self, parent: MutPtr<QStandardItem>, child: I)
+    where
+        I: ToQStringOwned,
+    {
+        if let Ok(view) = self.live() {
+            view.add_child(parent, child);
+        }
+    }
+
+    #[allow(dead_code)]
+    /// Set combobox sites, replacing any extant sites
+    ///
+    /// # Arguments
+    /// * `items` - Vector of items
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_sites<'c, I>(&self, items: Vec<I>, current: I)
+    where
+        I: AsRef<str>,
+    {
+        if let Ok(view) = self.live() {
+            view.set_sites(items, current);
+        }
+    }
+
+    #[allow(dead_code)]
+    /// Remove all sites from the combobox
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns None
+    pub fn remove_sites(&self) {
+        if let Ok(view) = self.live() {
+            view.remove_sites();
+        }
+    }
+
+    /// Change the max number of items displayed in the combobox's dropdown
+    /// list
+    ///
+    /// # Arguments
+    /// * `max` - Maximum number of visible items in the comobobox's dropdown
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_cb_max_visible_items(&self, max: i32) {
+        if let Ok(view) = self.live() {
+            view.set_cb_max_visible_items(max);
+        }
     }
 }
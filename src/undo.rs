@@ -0,0 +1,204 @@
+use crate::edit::PinRequest;
+
+/// The default maximum number of changes retained by the UndoStack
+pub const DEFAULT_UNDO_LIMIT: usize = 100;
+
+/// A pin change made through the tree, along with the state it replaced, so
+/// that it may be reverted (undone) and reapplied (redone).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinChange {
+    /// The pin which was written
+    pub request: PinRequest,
+    /// The version which was pinned at the request's coordinates beforehand, if any
+    pub previous: Option<String>,
+}
+
+impl PinChange {
+    /// New up a PinChange
+    ///
+    /// # Arguments
+    /// * `request` - The pin which was written
+    /// * `previous` - The version previously pinned at the same coordinates, if any
+    ///
+    /// # Returns
+    /// * PinChange instance
+    pub fn new(request: PinRequest, previous: Option<String>) -> Self {
+        Self { request, previous }
+    }
+
+    /// Retrieve the request which reverts the change, or None if reverting
+    /// it requires removing the pin
+    pub fn revert_request(&self) -> Option<PinRequest> {
+        self.previous.as_ref().map(|version| PinRequest {
+            version: version.clone(),
+            ..self.request.clone()
+        })
+    }
+
+    /// Describe the change (eg `pin houdini-18.0.2 for cent7_64 at lighting @ dev01 (portland)`)
+    pub fn describe(&self) -> String {
+        let request = &self.request;
+        format!(
+            "pin {}-{} for {} at {} ({})",
+            request.package,
+            request.version,
+            request.platform,
+            request.label(),
+            request.site
+        )
+    }
+}
+
+/// The undo and redo stacks of pin changes made through the tree. The most
+/// recent change is last. Making a new change discards the redo stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoStack {
+    undo: Vec<PinChange>,
+    redo: Vec<PinChange>,
+    limit: usize,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::with_limit(DEFAULT_UNDO_LIMIT)
+    }
+}
+
+impl UndoStack {
+    /// New up an empty UndoStack retaining at most `limit` changes
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Retrieve the maximum number of changes retained
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Retrieve the changes which may be undone, oldest first
+    pub fn undo_changes(&self) -> &[PinChange] {
+        &self.undo
+    }
+
+    /// Retrieve the changes which may be redone, oldest first
+    pub fn redo_changes(&self) -> &[PinChange] {
+        &self.redo
+    }
+
+    /// Determine whether there is a change to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Determine whether there is a change to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Record a new change, discarding the redo stack and the oldest change
+    /// if the limit has been reached
+    pub(crate) fn push(&mut self, change: PinChange) {
+        self.redo.clear();
+        self.undo.push(change);
+        if self.undo.len() > self.limit {
+            let excess = self.undo.len() - self.limit;
+            self.undo.drain(0..excess);
+        }
+    }
+
+    /// Retrieve the change which would be undone next
+    pub fn next_undo(&self) -> Option<&PinChange> {
+        self.undo.last()
+    }
+
+    /// Retrieve the change which would be redone next
+    pub fn next_redo(&self) -> Option<&PinChange> {
+        self.redo.last()
+    }
+
+    /// Move the most recent change to the redo stack once it has been undone
+    pub(crate) fn undone(&mut self) {
+        if let Some(change) = self.undo.pop() {
+            self.redo.push(change);
+        }
+    }
+
+    /// Move the most recently undone change back to the undo stack once it has been redone
+    pub(crate) fn redone(&mut self) {
+        if let Some(change) = self.redo.pop() {
+            self.undo.push(change);
+        }
+    }
+
+    /// Discard all of the changes
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(version: &str, previous: Option<&str>) -> PinChange {
+        PinChange::new(
+            PinRequest::new("maya", version, "any", "portland", "any", "facility"),
+            previous.map(|previous| previous.to_string()),
+        )
+    }
+
+    #[test]
+    fn changes_move_between_the_stacks() {
+        let mut stack = UndoStack::default();
+        assert!(!stack.can_undo() && !stack.can_redo());
+        stack.push(change("2020.1", None));
+        stack.push(change("2020.2", Some("2020.1")));
+        assert_eq!(stack.next_undo(), Some(&change("2020.2", Some("2020.1"))));
+        stack.undone();
+        assert_eq!(stack.next_redo(), Some(&change("2020.2", Some("2020.1"))));
+        assert_eq!(stack.undo_changes(), &[change("2020.1", None)]);
+        stack.redone();
+        assert!(!stack.can_redo());
+        assert_eq!(stack.undo_changes().len(), 2);
+    }
+
+    #[test]
+    fn pushing_discards_the_redo_stack() {
+        let mut stack = UndoStack::default();
+        stack.push(change("2020.1", None));
+        stack.undone();
+        assert!(stack.can_redo());
+        stack.push(change("2020.3", None));
+        assert!(!stack.can_redo());
+        stack.clear();
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn the_oldest_changes_are_dropped_beyond_the_limit() {
+        let mut stack = UndoStack::with_limit(2);
+        for version in &["1", "2", "3"] {
+            stack.push(change(version, None));
+        }
+        assert_eq!(
+            stack.undo_changes(),
+            &[change("2", None), change("3", None)]
+        );
+    }
+
+    #[test]
+    fn reverting_restores_the_previous_version() {
+        assert_eq!(
+            change("2020.2", Some("2020.1")).revert_request(),
+            Some(PinRequest::new(
+                "maya", "2020.1", "any", "portland", "any", "facility"
+            ))
+        );
+        assert_eq!(change("2020.2", None).revert_request(), None);
+    }
+}
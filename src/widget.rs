@@ -0,0 +1,76 @@
+use qt_core::{QObject, SlotOfQObject};
+use qt_widgets::cpp_core::{MutPtr, StaticUpcast};
+use std::cell::Cell;
+use std::fmt;
+use std::rc::Rc;
+
+/// Errors raised when creating, placing or accessing the tree's widgets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetError {
+    /// The parent widget has no layout to which the tree may be added
+    MissingLayout,
+    /// The tree's widgets have been destroyed by Qt (eg along with their parent)
+    Destroyed,
+}
+
+impl fmt::Display for WidgetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WidgetError::MissingLayout => write!(f, "the parent widget does not have a layout"),
+            WidgetError::Destroyed => write!(f, "the widget has been destroyed"),
+        }
+    }
+}
+
+impl std::error::Error for WidgetError {}
+
+/// A pointer to a QObject which tracks whether the object has been destroyed,
+/// by listening to its `destroyed` signal. Qt owns the object; dereferencing
+/// a Guarded pointer is only safe while it is alive.
+pub(crate) struct Guarded<T> {
+    ptr: MutPtr<T>,
+    alive: Rc<Cell<bool>>,
+    _destroyed: SlotOfQObject<'static>,
+}
+
+impl<T> Guarded<T>
+where
+    T: StaticUpcast<QObject>,
+{
+    /// Guard the supplied pointer, which must be valid
+    pub(crate) unsafe fn new(ptr: MutPtr<T>) -> Self {
+        let alive = Rc::new(Cell::new(!ptr.is_null()));
+        let flag = alive.clone();
+        let destroyed = SlotOfQObject::new(move |_object: MutPtr<QObject>| {
+            flag.set(false);
+        });
+        if !ptr.is_null() {
+            let object: MutPtr<QObject> = ptr.static_upcast_mut();
+            object.destroyed().connect(&destroyed);
+        }
+        Self {
+            ptr,
+            alive,
+            _destroyed: destroyed,
+        }
+    }
+
+    /// Determine whether the object is still alive
+    pub(crate) fn is_alive(&self) -> bool {
+        self.alive.get()
+    }
+
+    /// Retrieve the pointer, or WidgetError::Destroyed if the object has been destroyed
+    pub(crate) fn get(&self) -> Result<MutPtr<T>, WidgetError> {
+        if self.is_alive() {
+            Ok(self.ptr)
+        } else {
+            Err(WidgetError::Destroyed)
+        }
+    }
+
+    /// Retrieve the pointer without checking whether the object is alive
+    pub(crate) fn as_mut_ptr(&self) -> MutPtr<T> {
+        self.ptr
+    }
+}
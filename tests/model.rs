@@ -0,0 +1,122 @@
+//! Exercises the model behind the tree with an offscreen QApplication.
+//! Qt requires the application to be created, and used, on the main thread,
+//! so the checks run in sequence from main rather than under the test harness.
+use pbgui_tree::tree::DistributionTreeView;
+use qt_gui::{QStandardItem, QStandardItemModel};
+use qt_widgets::cpp_core::MutPtr;
+use qt_widgets::QApplication;
+
+// the text of the top level items, in order
+unsafe fn top_level_names(model: MutPtr<QStandardItemModel>) -> Vec<String> {
+    (0..model.row_count_0a())
+        .map(|row| model.item_2a(row, 0).text().to_std_string())
+        .collect()
+}
+
+// whether the item holds nothing but the empty placeholder child
+unsafe fn has_placeholder(item: MutPtr<QStandardItem>) -> bool {
+    item.row_count() == 1 && item.child_2a(0, 0).text().to_std_string() == ""
+}
+
+unsafe fn set_packages_adds_expandable_rows() {
+    let tree = DistributionTreeView::new();
+    tree.set_packages(vec!["maya", "nuke", "maya", "houdini"]);
+    let model = tree.model().expect("the tree is alive");
+    assert_eq!(top_level_names(model), vec!["maya", "nuke", "houdini"]);
+    for row in 0..model.row_count_0a() {
+        assert!(has_placeholder(model.item_2a(row, 0)));
+    }
+}
+
+unsafe fn add_and_remove_package() {
+    let tree = DistributionTreeView::new();
+    tree.set_packages(vec!["maya"]);
+    tree.add_package("nuke");
+    tree.add_package("maya");
+    let model = tree.model().expect("the tree is alive");
+    assert_eq!(top_level_names(model), vec!["maya", "nuke"]);
+    assert!(has_placeholder(model.item_2a(1, 0)));
+
+    assert!(tree.remove_package("maya"));
+    assert!(!tree.remove_package("maya"));
+    assert_eq!(top_level_names(model), vec!["nuke"]);
+}
+
+unsafe fn clear_preserves_the_columns() {
+    let tree = DistributionTreeView::new();
+    let model = tree.model().expect("the tree is alive");
+    let columns = model.column_count_0a();
+    tree.set_packages(vec!["maya", "nuke"]);
+    tree.clear();
+    assert_eq!(model.row_count_0a(), 0);
+    assert_eq!(model.column_count_0a(), columns);
+
+    tree.set_multi_column(true);
+    assert_eq!(model.column_count_0a(), 6);
+    tree.clear();
+    assert_eq!(model.column_count_0a(), 6);
+}
+
+unsafe fn set_packages_keeps_the_extant_rows() {
+    let tree = DistributionTreeView::new();
+    tree.set_packages(vec!["b", "d"]);
+    let model = tree.model().expect("the tree is alive");
+    // stands in for a loaded version
+    tree.add_child(model.item_2a(0, 0), "1.0.0");
+
+    tree.set_packages(vec!["a", "b", "c", "d", "e"]);
+    assert_eq!(top_level_names(model), vec!["a", "b", "c", "d", "e"]);
+    let extant = model.item_2a(1, 0);
+    assert_eq!(extant.row_count(), 2);
+    assert_eq!(extant.child_2a(1, 0).text().to_std_string(), "1.0.0");
+    assert!(has_placeholder(model.item_2a(0, 0)));
+
+    tree.set_packages(vec!["c", "x"]);
+    assert_eq!(top_level_names(model), vec!["c", "x"]);
+}
+
+unsafe fn multi_column_keeps_the_rows() {
+    let tree = DistributionTreeView::new();
+    tree.set_packages(vec!["maya", "nuke"]);
+    let model = tree.model().expect("the tree is alive");
+    tree.add_child(model.item_2a(0, 0), "1.0.0");
+
+    tree.set_multi_column(true);
+    assert_eq!(model.column_count_0a(), 6);
+    assert_eq!(model.row_count_0a(), 2);
+    tree.set_multi_column(false);
+    assert_eq!(model.column_count_0a(), 2);
+    // the header may have sorted the rows
+    let mut names = top_level_names(model);
+    names.sort();
+    assert_eq!(names, vec!["maya", "nuke"]);
+    let maya = (0..model.row_count_0a())
+        .map(|row| model.item_2a(row, 0))
+        .find(|item| item.text().to_std_string() == "maya")
+        .expect("maya is kept");
+    assert_eq!(maya.row_count(), 2);
+}
+
+fn main() {
+    std::env::set_var("QT_QPA_PLATFORM", "offscreen");
+    QApplication::init(|_app| unsafe {
+        let checks: [(&str, unsafe fn()); 5] = [
+            (
+                "set_packages_adds_expandable_rows",
+                set_packages_adds_expandable_rows,
+            ),
+            ("add_and_remove_package", add_and_remove_package),
+            ("clear_preserves_the_columns", clear_preserves_the_columns),
+            (
+                "set_packages_keeps_the_extant_rows",
+                set_packages_keeps_the_extant_rows,
+            ),
+            ("multi_column_keeps_the_rows", multi_column_keeps_the_rows),
+        ];
+        for (name, check) in checks.iter() {
+            check();
+            println!("test {} ... ok", name);
+        }
+        0
+    });
+}
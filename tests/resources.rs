@@ -0,0 +1,145 @@
+//! Checks that the prebuilt rcc file, which is embedded when qt's rcc tool is
+//! unavailable, holds every file listed in the qrc file, as it currently is on
+//! disk. Regenerate it via `make rcc` if this fails.
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fs;
+
+const QRC: &str = "resources/pbgui_tree.qrc";
+const PREBUILT_RCC: &str = "resources/pbgui_tree.rcc";
+
+// the flags of the entries in the tree of an rcc file
+const COMPRESSED: u16 = 0x01;
+const DIRECTORY: u16 = 0x02;
+
+// a file stored in the rcc file
+#[derive(Debug)]
+enum Stored {
+    Plain(Vec<u8>),
+    // compressed with zlib; only the uncompressed length is checked
+    Compressed { len: usize },
+}
+
+// reads the big endian numbers of an rcc file
+struct Rcc<'a> {
+    data: &'a [u8],
+    version: u32,
+    tree: usize,
+    blobs: usize,
+    names: usize,
+}
+
+impl<'a> Rcc<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        assert_eq!(&data[..4], b"qres", "{} is not an rcc file", PREBUILT_RCC);
+        let mut rcc = Self {
+            data,
+            version: 0,
+            tree: 0,
+            blobs: 0,
+            names: 0,
+        };
+        rcc.version = rcc.u32_at(4);
+        rcc.tree = rcc.u32_at(8) as usize;
+        rcc.blobs = rcc.u32_at(12) as usize;
+        rcc.names = rcc.u32_at(16) as usize;
+        rcc
+    }
+
+    fn u16_at(&self, offset: usize) -> u16 {
+        u16::from_be_bytes(self.data[offset..offset + 2].try_into().unwrap())
+    }
+
+    fn u32_at(&self, offset: usize) -> u32 {
+        u32::from_be_bytes(self.data[offset..offset + 4].try_into().unwrap())
+    }
+
+    // the names are stored as UTF-16BE, preceded by their length and hash
+    fn name(&self, offset: usize) -> String {
+        let start = self.names + offset;
+        let len = self.u16_at(start) as usize;
+        let units = (0..len)
+            .map(|unit| self.u16_at(start + 6 + unit * 2))
+            .collect::<Vec<_>>();
+        String::from_utf16(&units).expect("the name is not valid UTF-16")
+    }
+
+    // the offset of the entry at the supplied index of the tree
+    fn entry(&self, index: usize) -> usize {
+        let size = if self.version >= 2 { 22 } else { 14 };
+        self.tree + index * size
+    }
+
+    // collect the files beneath the entry at the supplied index
+    fn collect(&self, index: usize, path: &str, files: &mut BTreeMap<String, Stored>) {
+        let entry = self.entry(index);
+        let path = if index == 0 {
+            String::new()
+        } else {
+            let name = self.name(self.u32_at(entry) as usize);
+            if path.is_empty() {
+                name
+            } else {
+                format!("{}/{}", path, name)
+            }
+        };
+        let flags = self.u16_at(entry + 4);
+        if flags & DIRECTORY != 0 {
+            let count = self.u32_at(entry + 6) as usize;
+            let first = self.u32_at(entry + 10) as usize;
+            for child in first..first + count {
+                self.collect(child, &path, files);
+            }
+            return;
+        }
+        let blob = self.blobs + self.u32_at(entry + 10) as usize;
+        let len = self.u32_at(blob) as usize;
+        let stored = if flags & COMPRESSED != 0 {
+            Stored::Compressed {
+                len: self.u32_at(blob + 4) as usize,
+            }
+        } else {
+            Stored::Plain(self.data[blob + 4..blob + 4 + len].to_vec())
+        };
+        files.insert(path, stored);
+    }
+}
+
+// retrieve the paths of the files listed in the qrc file
+fn qrc_files() -> Vec<String> {
+    let qrc = fs::read_to_string(QRC).expect("unable to read the qrc file");
+    qrc.split("<file>")
+        .skip(1)
+        .filter_map(|rest| rest.split("</file>").next())
+        .map(|file| file.trim().to_string())
+        .collect()
+}
+
+#[test]
+fn prebuilt_rcc_matches_the_qrc() {
+    let data = fs::read(PREBUILT_RCC).expect("unable to read the prebuilt rcc file");
+    let mut stored = BTreeMap::new();
+    Rcc::new(&data).collect(0, "", &mut stored);
+
+    let mut listed = qrc_files();
+    listed.sort();
+    assert_eq!(
+        stored.keys().cloned().collect::<Vec<_>>(),
+        listed,
+        "{} lists different files than {}; regenerate it via `make rcc`",
+        PREBUILT_RCC,
+        QRC
+    );
+    for (path, stored) in stored.iter() {
+        let contents = fs::read(format!("resources/{}", path)).expect("unable to read a resource");
+        let current = match stored {
+            Stored::Plain(bytes) => *bytes == contents,
+            Stored::Compressed { len } => *len == contents.len(),
+        };
+        assert!(
+            current,
+            "{} holds a stale copy of {}; regenerate it via `make rcc`",
+            PREBUILT_RCC, path
+        );
+    }
+}